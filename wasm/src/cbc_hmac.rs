@@ -0,0 +1,131 @@
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+
+use crate::error::Error;
+use crate::hash;
+use crate::padding::{self, BLOCK_SIZE};
+use crate::Aes256CbcDec;
+
+/// 记录末尾HMAC-SHA256认证标签的长度
+pub(crate) const MAC_SIZE: usize = 32;
+
+/// 记录的最小长度：IV(16) + 至少一个密文块(16) + 认证标签(32)
+pub(crate) const MIN_RECORD_SIZE: usize = BLOCK_SIZE * 2 + MAC_SIZE;
+
+/// 校验并解密`IV(16) || CBC密文 || HMAC-SHA256(32)`格式的记录（先加密后MAC）
+/// 认证标签为`HMAC-SHA256(mac_key, AAD || IV || 密文 || AL)`，其中AL为AAD的比特长度（64位大端），
+/// 与AEAD_AES_CBC_HMAC_SHA2草案的构造一致但不截断标签。先以常量时间比较认证标签，通过后才解密
+pub(crate) fn open(enc_key: &[u8], mac_key: &[u8], record: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+    if record.len() < MIN_RECORD_SIZE {
+        return Err(Error::CbcHmacTooShort(record.len()));
+    }
+
+    let (iv, rest) = record.split_at(BLOCK_SIZE);
+    let (ciphertext, tag) = rest.split_at(rest.len() - MAC_SIZE);
+    if !ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(Error::UnalignedData);
+    }
+
+    let aad_bits = (aad.len() as u64 * 8).to_be_bytes();
+    let expected = hash::hmac_sha256_parts(mac_key, &[aad, iv, ciphertext, &aad_bits]);
+    let diff = tag.iter().zip(expected.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 {
+        return Err(Error::MacMismatch);
+    }
+
+    let mut buffer = ciphertext.to_vec();
+    Aes256CbcDec::new_from_slices(enc_key, iv)
+        .map_err(|e| Error::CipherInit(e.to_string()))?
+        .decrypt_padded_mut::<NoPadding>(&mut buffer)
+        .map_err(|_e| Error::UnalignedData)?;
+
+    let len = padding::pkcs7_unpadded_len(&buffer).map_err(|fault| Error::Padding {
+        fault,
+        len: ciphertext.len(),
+        last_block: ciphertext[ciphertext.len() - BLOCK_SIZE..].to_vec(),
+    })?;
+    buffer.truncate(len);
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        let digits: Vec<u8> = text.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect();
+        digits
+            .chunks_exact(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    /// 加密密钥为0x20~0x3f，MAC密钥为0x40~0x5f，IV为0xa0~0xaf
+    fn keys() -> (Vec<u8>, Vec<u8>) {
+        ((0x20..0x40).collect(), (0x40..0x60).collect())
+    }
+
+    const PLAINTEXT: &[u8] = b"Encrypt-then-MAC with AES-256-CBC";
+
+    /// AAD为"header:v1"时的记录
+    const RECORD: &str = "a0a1a2a3a4a5a6a7a8a9aaabacadaeaf561d8fdbea185f21e058ad35f4cb8ab1\
+                          135b7179b56933972c254b83bbb152b49b9a317fbe9bb41a1386068378616650\
+                          153fa183f4636d2f207c89cbd5ae1f7a111572b0cd79ce3bfc6c98254d7f3dbc";
+
+    /// 相同密钥、IV和明文，AAD为空时的记录，只有认证标签不同
+    const RECORD_EMPTY_AAD: &str = "a0a1a2a3a4a5a6a7a8a9aaabacadaeaf561d8fdbea185f21e058ad35f4cb8ab1\
+                                    135b7179b56933972c254b83bbb152b49b9a317fbe9bb41a1386068378616650\
+                                    fd385ab4e8f7e2d0cb8b8f42e0a2f97db6cd3399aecd940cbf1960f70fbc2a6d";
+
+    #[test]
+    fn opens_known_record() {
+        let (enc_key, mac_key) = keys();
+        let plaintext = open(&enc_key, &mac_key, &hex(RECORD), b"header:v1").unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn opens_known_record_with_empty_aad() {
+        let (enc_key, mac_key) = keys();
+        let plaintext = open(&enc_key, &mac_key, &hex(RECORD_EMPTY_AAD), b"").unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn rejects_wrong_aad() {
+        let (enc_key, mac_key) = keys();
+        assert!(matches!(open(&enc_key, &mac_key, &hex(RECORD), b"header:v2"), Err(Error::MacMismatch)));
+        assert!(matches!(open(&enc_key, &mac_key, &hex(RECORD_EMPTY_AAD), b"header:v1"), Err(Error::MacMismatch)));
+    }
+
+    #[test]
+    fn rejects_tampered_record() {
+        let (enc_key, mac_key) = keys();
+
+        // IV、密文和标签中任意一位被修改都应在解密前被拒绝
+        for index in [0, BLOCK_SIZE, MIN_RECORD_SIZE, hex(RECORD).len() - 1] {
+            let mut record = hex(RECORD);
+            record[index] ^= 0x01;
+            assert!(matches!(open(&enc_key, &mac_key, &record, b"header:v1"), Err(Error::MacMismatch)));
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_mac_key() {
+        let (enc_key, _) = keys();
+        assert!(matches!(open(&enc_key, &[0; 32], &hex(RECORD), b"header:v1"), Err(Error::MacMismatch)));
+    }
+
+    #[test]
+    fn rejects_short_and_unaligned_records() {
+        let (enc_key, mac_key) = keys();
+        let record = hex(RECORD);
+
+        assert!(matches!(
+            open(&enc_key, &mac_key, &record[..MIN_RECORD_SIZE - 1], b""),
+            Err(Error::CbcHmacTooShort(len)) if len == MIN_RECORD_SIZE - 1
+        ));
+        assert!(matches!(open(&enc_key, &mac_key, &record[1..], b""), Err(Error::UnalignedData)));
+    }
+}
@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Locale};
+use crate::padding::{self, BLOCK_SIZE};
+use crate::IvMode;
+
+/// 解密器默认配置
+/// 创建一次后传给`ImageDecryptor.with_config`，即可让多个实例使用相同的设置，
+/// 无需逐个调用set_*方法。各项默认值与`new ImageDecryptor()`相同
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct DecryptorConfig {
+    pub(crate) locale: Locale,
+    pub(crate) max_input_size: Option<usize>,
+    pub(crate) iv_mode: IvMode,
+    pub(crate) strict_iv: bool,
+    pub(crate) trim_trailing_newline: bool,
+    pub(crate) pad_block_size: usize,
+}
+
+#[wasm_bindgen]
+impl DecryptorConfig {
+    /// 创建默认配置
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> DecryptorConfig {
+        DecryptorConfig {
+            locale: Locale::default(),
+            max_input_size: None,
+            iv_mode: IvMode::Explicit,
+            strict_iv: true,
+            trim_trailing_newline: false,
+            pad_block_size: BLOCK_SIZE,
+        }
+    }
+
+    /// 设置错误信息语言，支持"zh"（默认）和"en"
+    #[wasm_bindgen]
+    pub fn set_locale(&mut self, lang: &str) -> Result<(), JsValue> {
+        self.locale = Locale::parse(lang)
+            .ok_or_else(|| Error::UnsupportedLocale(lang.to_string()).to_js(self.locale))?;
+        Ok(())
+    }
+
+    /// 设置允许解密的最大输入字节数，默认不限制
+    #[wasm_bindgen]
+    pub fn set_max_input_size(&mut self, bytes: usize) {
+        self.max_input_size = Some(bytes);
+    }
+
+    /// 设置IV来源模式，默认为Explicit
+    #[wasm_bindgen]
+    pub fn set_iv_mode(&mut self, mode: IvMode) {
+        self.iv_mode = mode;
+    }
+
+    /// 设置是否严格校验IV长度，默认为true，含义见`ImageDecryptor.set_strict_iv`
+    #[wasm_bindgen]
+    pub fn set_strict_iv(&mut self, strict: bool) {
+        self.strict_iv = strict;
+    }
+
+    /// 设置是否去掉明文末尾的一个换行符，默认为false，含义见`ImageDecryptor.set_trim_trailing_newline`
+    #[wasm_bindgen]
+    pub fn set_trim_trailing_newline(&mut self, trim: bool) {
+        self.trim_trailing_newline = trim;
+    }
+
+    /// 设置PKCS7填充的分组长度，只能为8或16，默认为16，含义见`ImageDecryptor.set_padding_block_size`
+    #[wasm_bindgen]
+    pub fn set_padding_block_size(&mut self, size: usize) -> Result<(), JsValue> {
+        self.pad_block_size = padding::check_block_size(size).map_err(|e| e.to_js(self.locale))?;
+        Ok(())
+    }
+}
+
+impl Default for DecryptorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 工具函数：获取默认配置
+/// 
+/// # 返回
+/// 与`new ImageDecryptor()`相同设置的配置对象
+#[wasm_bindgen]
+pub fn default_config() -> DecryptorConfig {
+    DecryptorConfig::new()
+}
@@ -0,0 +1,182 @@
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+/// AES-CTR计数器格式
+/// 各方实现对计数器的宽度和字节序约定不同，格式不一致会得到完全错误的明文。
+/// 大端格式（`Be*`）把16字节IV末尾的N位当作大端计数器递增，是NIST SP 800-38A的标准做法；
+/// 小端格式（`Le*`）把IV开头的N位当作小端计数器递增。IV其余部分作为固定nonce
+/// 
+/// - `Be128` / `Le128`: 整个16字节块作为计数器（默认`Be128`）
+/// - `Be64` / `Le64`: 64位计数器
+/// - `Be32` / `Le32`: 32位计数器
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CtrCounter {
+    Be128,
+    Le128,
+    Be64,
+    Le64,
+    Be32,
+    Le32,
+}
+
+/// 按指定计数器格式对数据原地应用AES-256-CTR密钥流（加密与解密相同）
+pub(crate) fn apply_keystream(counter: CtrCounter, key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), Error> {
+    match counter {
+        CtrCounter::Be128 => apply::<ctr::Ctr128BE<Aes256>>(key, iv, data),
+        CtrCounter::Le128 => apply::<ctr::Ctr128LE<Aes256>>(key, iv, data),
+        CtrCounter::Be64 => apply::<ctr::Ctr64BE<Aes256>>(key, iv, data),
+        CtrCounter::Le64 => apply::<ctr::Ctr64LE<Aes256>>(key, iv, data),
+        CtrCounter::Be32 => apply::<ctr::Ctr32BE<Aes256>>(key, iv, data),
+        CtrCounter::Le32 => apply::<ctr::Ctr32LE<Aes256>>(key, iv, data),
+    }
+}
+
+fn apply<C: KeyIvInit + StreamCipher>(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), Error> {
+    let mut cipher = C::new_from_slices(key, iv).map_err(|e| Error::CipherInit(e.to_string()))?;
+    cipher
+        .try_apply_keystream(data)
+        .map_err(|e| Error::CipherInit(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::{BlockEncrypt, KeyInit};
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+    }
+
+    const ALL: [CtrCounter; 6] = [
+        CtrCounter::Be128,
+        CtrCounter::Le128,
+        CtrCounter::Be64,
+        CtrCounter::Le64,
+        CtrCounter::Be32,
+        CtrCounter::Le32,
+    ];
+
+    /// 计数器宽度（字节）和是否为大端
+    fn layout(counter: CtrCounter) -> (usize, bool) {
+        match counter {
+            CtrCounter::Be128 => (16, true),
+            CtrCounter::Le128 => (16, false),
+            CtrCounter::Be64 => (8, true),
+            CtrCounter::Le64 => (8, false),
+            CtrCounter::Be32 => (4, true),
+            CtrCounter::Le32 => (4, false),
+        }
+    }
+
+    /// 逐块加密计数器生成期望的密钥流，计数器按宽度回绕，其余字节保持不变
+    fn reference_keystream(counter: CtrCounter, key: &[u8], iv: &[u8], blocks: usize) -> Vec<u8> {
+        let (width, big_endian) = layout(counter);
+        let cipher = Aes256::new_from_slice(key).unwrap();
+        let mut block: [u8; 16] = iv.try_into().unwrap();
+        let mut keystream = Vec::new();
+
+        for _ in 0..blocks {
+            let mut output = block;
+            cipher.encrypt_block((&mut output).into());
+            keystream.extend_from_slice(&output);
+
+            // 从最低有效字节开始加1并向高位进位，超出宽度的进位丢弃
+            let counter_bytes = if big_endian { &mut block[16 - width..] } else { &mut block[..width] };
+            let indices: Vec<usize> = if big_endian { (0..width).rev().collect() } else { (0..width).collect() };
+            for i in indices {
+                counter_bytes[i] = counter_bytes[i].wrapping_add(1);
+                if counter_bytes[i] != 0 {
+                    break;
+                }
+            }
+        }
+
+        keystream
+    }
+
+    /// NIST SP 800-38A F.5.5 CTR-AES256.Encrypt
+    #[test]
+    fn big_endian_counters_match_nist_vector() {
+        let key = hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+        let iv = hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = hex(concat!(
+            "6bc1bee22e409f96e93d7e117393172a",
+            "ae2d8a571e03ac9c9eb76fac45af8e51",
+            "30c81c46a35ce411e5fbc1191a0a52ef",
+            "f69f2445df4f9b17ad2b417be66c3710",
+        ));
+        let ciphertext = hex(concat!(
+            "601ec313775789a5b7a7f504bbf3d228",
+            "f443e3ca4d62b59aca84e990cacaf5c5",
+            "2b0930daa23de94ce87017ba2d84988d",
+            "dfc9c58db67aada613c2dd08457941a6",
+        ));
+
+        // 四个块内计数器不会越过32位，三种大端宽度结果相同
+        for counter in [CtrCounter::Be128, CtrCounter::Be64, CtrCounter::Be32] {
+            let mut data = plaintext.clone();
+            apply_keystream(counter, &key, &iv, &mut data).unwrap();
+            assert_eq!(data, ciphertext, "{:?}", counter);
+        }
+    }
+
+    #[test]
+    fn counters_match_reference_across_wraparound() {
+        let key: Vec<u8> = (0..32).collect();
+        // 每种宽度的计数器都从全FF开始，第二块起发生回绕或进位
+        let ivs = [
+            vec![0xFF; 16],
+            hex("00112233445566778899aabbffffffff"),
+            hex("ffffffff445566778899aabbccddeeff"),
+        ];
+
+        for counter in ALL {
+            for iv in &ivs {
+                let mut data = vec![0u8; 48];
+                apply_keystream(counter, &key, iv, &mut data).unwrap();
+                assert_eq!(data, reference_keystream(counter, &key, iv, 3), "{:?}", counter);
+            }
+        }
+    }
+
+    #[test]
+    fn counter_width_changes_keystream_after_wraparound() {
+        let key = [0x42u8; 32];
+        let iv = hex("00112233445566778899aabbffffffff");
+        let keystream = |counter| {
+            let mut data = vec![0u8; 32];
+            apply_keystream(counter, &key, &iv, &mut data).unwrap();
+            data
+        };
+
+        // 第一块相同，第二块32位计数器回绕而64/128位计数器向高位进位
+        assert_eq!(keystream(CtrCounter::Be32)[..16], keystream(CtrCounter::Be128)[..16]);
+        assert_ne!(keystream(CtrCounter::Be32)[16..], keystream(CtrCounter::Be64)[16..]);
+        assert_eq!(keystream(CtrCounter::Be64), keystream(CtrCounter::Be128));
+    }
+
+    #[test]
+    fn keystream_round_trips() {
+        let key = [7u8; 32];
+        let iv = [9u8; 16];
+        let plaintext: Vec<u8> = (0..100).collect();
+
+        for counter in ALL {
+            let mut data = plaintext.clone();
+            apply_keystream(counter, &key, &iv, &mut data).unwrap();
+            assert_ne!(data, plaintext);
+            apply_keystream(counter, &key, &iv, &mut data).unwrap();
+            assert_eq!(data, plaintext, "{:?}", counter);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_key_length() {
+        let mut data = [0u8; 16];
+        assert!(matches!(apply_keystream(CtrCounter::Be128, &[0; 16], &[0; 16], &mut data), Err(Error::CipherInit(_))));
+    }
+}
@@ -0,0 +1,272 @@
+use wasm_bindgen::JsValue;
+
+use crate::padding::PaddingFault;
+
+/// 错误信息语言
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Locale {
+    /// 解析语言代码，支持"zh"和"en"（忽略大小写和地区后缀，如"en-US"）
+    pub(crate) fn parse(lang: &str) -> Option<Locale> {
+        let primary = lang.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Locale::Zh),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+}
+
+/// 模块内统一的错误类型
+/// 所有面向调用方的错误信息集中在这里，新增语言只需补充对应的翻译函数
+#[derive(Debug)]
+pub(crate) enum Error {
+    EmptyKey,
+    EmptyIv,
+    EmptyData,
+    UnalignedData,
+    InputTooLarge { len: usize, max: usize },
+    KeyBase64(String),
+    IvBase64(String),
+    KeyLength(usize),
+    IvLength(usize),
+    CipherInit(String),
+    EmptyBuffer,
+    Padding { fault: PaddingFault, len: usize, last_block: Vec<u8> },
+    Decode(String),
+    InvalidRowAlignment(u32),
+    NoCanvasContext,
+    InvalidToken(u32),
+    UnsupportedLocale(String),
+    UnsupportedHashAlgorithm(String),
+    MissingIvBlock(usize),
+    NoGlobalScope,
+    SpriteIndexTruncated,
+    SpriteEntryOutOfBounds { index: usize, offset: usize, len: usize, total: usize },
+    InsufficientCapacity { needed: usize, offset: usize, capacity: usize },
+    InvalidLayout(String),
+    LayoutExceedsData { needed: usize, len: usize },
+    Encode(String),
+    GcmTooShort(usize),
+    GcmAuthFailed,
+    KeyIdLength(usize),
+    MissingKeyId(usize),
+    UnknownKeyId(String),
+    UnsupportedFormat(String),
+    NotDataUrl,
+    DataUrlNotBase64,
+    DataUrlBase64(String),
+    UnknownImageType,
+    EmptySignature,
+    MissingCrc(usize),
+    CrcMismatch { expected: u32, actual: u32 },
+    PrevBlockLength(usize),
+    ChunkCallback,
+    Gunzip(String),
+    MissingLengthPrefix(usize),
+    LengthPrefixMismatch { declared: usize, available: usize },
+    UnknownDimensions,
+    DimensionMismatch { expected: (u32, u32), actual: (u32, u32) },
+    MissingFirstBlock(usize),
+    InvalidBatchItem(usize),
+    ZeroTileSize,
+    CbcHmacTooShort(usize),
+    MacMismatch,
+    GcmNonceLength(usize),
+    GcmCiphertextTooShort(usize),
+    PaddingBlockSize(usize),
+    EnvelopeJson,
+    MissingEnvelopeField(&'static str),
+    UnsupportedCipher(String),
+    EnvelopeDataBase64(String),
+    KeyIvSwapped,
+    GunzipTooLarge(usize),
+    PixelBufferTooLarge,
+    InvalidFrameBudget(f64),
+}
+
+impl Error {
+    /// 按指定语言生成错误信息
+    pub(crate) fn message(&self, locale: Locale) -> String {
+        match locale {
+            Locale::Zh => self.zh(),
+            Locale::En => self.en(),
+        }
+    }
+
+    /// 按指定语言转换为JS错误值
+    pub(crate) fn to_js(&self, locale: Locale) -> JsValue {
+        JsValue::from_str(&self.message(locale))
+    }
+
+    fn zh(&self) -> String {
+        match self {
+            Error::EmptyKey => "密钥不能为空".to_string(),
+            Error::EmptyIv => "IV不能为空".to_string(),
+            Error::EmptyData => "密文为空".to_string(),
+            Error::UnalignedData => "加密数据长度必须是16字节的倍数".to_string(),
+            Error::InputTooLarge { len, max } => {
+                format!("输入超过最大限制: 数据长度={}字节，最大限制={}字节", len, max)
+            }
+            Error::KeyBase64(e) => format!("密钥Base64解码失败: {}", e),
+            Error::IvBase64(e) => format!("IV Base64解码失败: {}", e),
+            Error::KeyLength(len) => format!("密钥长度必须为32字节，当前为{}字节", len),
+            Error::IvLength(len) => format!("IV长度必须为16字节，当前为{}字节", len),
+            Error::CipherInit(e) => format!("AES解密器初始化失败: {}", e),
+            Error::EmptyBuffer => "解密缓冲区为空".to_string(),
+            Error::Padding { fault, len, last_block } => {
+                let reason = match fault {
+                    PaddingFault::Length { pad, max } => format!("填充长度{}不在1~{}范围内", pad, max),
+                    PaddingFault::Byte { position, found, expected } => {
+                        format!("倒数第{}个字节为0x{:02x}，应为0x{:02x}", position, found, expected)
+                    }
+                };
+                format!("填充无效: {}; 数据长度={}, 最后16字节={:?}", reason, len, last_block)
+            }
+            Error::Decode(e) => format!("图片解码失败: {}", e),
+            Error::InvalidRowAlignment(alignment) => format!("行对齐字节数必须是不超过256的2的幂，当前为{}", alignment),
+            Error::NoCanvasContext => "无法获取OffscreenCanvas的2D上下文".to_string(),
+            Error::InvalidToken(token) => format!("无效的结果令牌: {}", token),
+            Error::UnsupportedLocale(lang) => format!("不支持的语言: {}", lang),
+            Error::UnsupportedHashAlgorithm(algo) => format!("不支持的哈希算法: {}", algo),
+            Error::MissingIvBlock(len) => format!("数据长度不足以包含IV块和密文: 当前为{}字节，至少需要32字节", len),
+            Error::NoGlobalScope => "无法获取全局作用域（Window或Worker）".to_string(),
+            Error::SpriteIndexTruncated => "图集索引表不完整".to_string(),
+            Error::SpriteEntryOutOfBounds { index, offset, len, total } => {
+                format!("图集第{}项越界: 偏移={}, 长度={}, 数据总长度={}", index, offset, len, total)
+            }
+            Error::InsufficientCapacity { needed, offset, capacity } => {
+                format!("目标缓冲区容量不足: 需要{}字节，偏移={}，容量={}字节", needed, offset, capacity)
+            }
+            Error::InvalidLayout(segment) => format!("无效的布局描述: {}", segment),
+            Error::LayoutExceedsData { needed, len } => format!("布局超出数据长度: 需要{}字节，实际为{}字节", needed, len),
+            Error::Encode(e) => format!("图片编码失败: {}", e),
+            Error::GcmTooShort(len) => format!("GCM数据长度不足: 当前为{}字节，至少需要28字节（nonce 12字节 + tag 16字节）", len),
+            Error::GcmAuthFailed => "GCM认证失败：密钥错误或数据已被篡改".to_string(),
+            Error::KeyIdLength(len) => format!("密钥ID长度必须为4字节，当前为{}字节", len),
+            Error::MissingKeyId(len) => format!("数据长度不足以包含4字节密钥ID和密文: 当前为{}字节", len),
+            Error::UnknownKeyId(kid) => format!("未知密钥ID: {}", kid),
+            Error::UnsupportedFormat(format) => format!("不支持的目标格式: {}", format),
+            Error::NotDataUrl => "不是有效的data URL，应以\"data:\"开头并包含\",\"".to_string(),
+            Error::DataUrlNotBase64 => "data URL缺少\";base64\"标记，只支持Base64编码的数据".to_string(),
+            Error::DataUrlBase64(e) => format!("data URL数据Base64解码失败: {}", e),
+            Error::UnknownImageType => "解密后的数据不是可识别的图片格式，密钥或IV可能不正确".to_string(),
+            Error::EmptySignature => "文件签名不能为空".to_string(),
+            Error::MissingCrc(len) => format!("明文长度不足以包含4字节CRC32: 当前为{}字节", len),
+            Error::CrcMismatch { expected, actual } => format!("CRC32校验失败: 期望0x{:08x}，实际0x{:08x}", expected, actual),
+            Error::PrevBlockLength(len) => format!("前一密文块长度必须为16字节，当前为{}字节", len),
+            Error::ChunkCallback => "分段回调抛出异常".to_string(),
+            Error::Gunzip(e) => format!("gzip解压失败: {}", e),
+            Error::MissingLengthPrefix(len) => format!("明文长度不足以包含4字节长度前缀: 当前为{}字节", len),
+            Error::LengthPrefixMismatch { declared, available } => format!("长度前缀与数据不一致: 声明{}字节，实际可用{}字节", declared, available),
+            Error::UnknownDimensions => "无法从文件头解析图片尺寸".to_string(),
+            Error::DimensionMismatch { expected, actual } => {
+                format!("图片尺寸不符: 期望{}x{}，实际{}x{}", expected.0, expected.1, actual.0, actual.1)
+            }
+            Error::MissingFirstBlock(len) => format!("数据长度不足一个完整的密文块: 当前为{}字节，至少需要16字节", len),
+            Error::InvalidBatchItem(index) => format!("第{}项格式无效: 应为Uint8Array或包含data和iv_base64字段的对象", index),
+            Error::ZeroTileSize => "瓦片尺寸必须大于0".to_string(),
+            Error::CbcHmacTooShort(len) => format!("记录长度不足: 当前为{}字节，至少需要64字节（16字节IV + 16字节密文 + 32字节认证标签）", len),
+            Error::MacMismatch => "HMAC校验失败: 密钥错误或数据被篡改".to_string(),
+            Error::GcmNonceLength(len) => format!("GCM需要12字节nonce，当前为{}字节（16字节IV仅用于CBC/CTR）", len),
+            Error::GcmCiphertextTooShort(len) => format!("GCM数据长度不足: 当前为{}字节，至少需要16字节认证标签", len),
+            Error::PaddingBlockSize(size) => format!("填充分组长度只能为8或16，当前为{}", size),
+            Error::EnvelopeJson => "信封不是合法的JSON".to_string(),
+            Error::MissingEnvelopeField(field) => format!("信封缺少字符串字段: {}", field),
+            Error::UnsupportedCipher(alg) => format!("不支持的加密算法: {}（支持AES-256-CBC、AES-256-GCM、AES-256-CTR）", alg),
+            Error::EnvelopeDataBase64(e) => format!("信封data字段Base64解码失败: {}", e),
+            Error::GunzipTooLarge(limit) => format!("gzip解压结果超过上限{}字节", limit),
+            Error::PixelBufferTooLarge => "对齐后的像素缓冲区大小超出可寻址范围".to_string(),
+            Error::InvalidFrameBudget(budget) => format!("每帧时间预算必须是大于0的有限数，当前为{}", budget),
+            Error::KeyIvSwapped => "密钥和IV可能调换了: 密钥解码为16字节、IV解码为32字节，请检查参数顺序".to_string(),
+        }
+    }
+
+    fn en(&self) -> String {
+        match self {
+            Error::EmptyKey => "Key must not be empty".to_string(),
+            Error::EmptyIv => "IV must not be empty".to_string(),
+            Error::EmptyData => "Ciphertext is empty".to_string(),
+            Error::UnalignedData => "Encrypted data length must be a multiple of 16 bytes".to_string(),
+            Error::InputTooLarge { len, max } => {
+                format!("Input exceeds maximum size: length={} bytes, limit={} bytes", len, max)
+            }
+            Error::KeyBase64(e) => format!("Failed to decode key from Base64: {}", e),
+            Error::IvBase64(e) => format!("Failed to decode IV from Base64: {}", e),
+            Error::KeyLength(len) => format!("Key must be 32 bytes, got {} bytes", len),
+            Error::IvLength(len) => format!("IV must be 16 bytes, got {} bytes", len),
+            Error::CipherInit(e) => format!("Failed to initialize AES decryptor: {}", e),
+            Error::EmptyBuffer => "Decryption buffer is empty".to_string(),
+            Error::Padding { fault, len, last_block } => {
+                let reason = match fault {
+                    PaddingFault::Length { pad, max } => format!("pad length {} is outside 1..={}", pad, max),
+                    PaddingFault::Byte { position, found, expected } => {
+                        format!("byte {} from the end is 0x{:02x}, expected 0x{:02x}", position, found, expected)
+                    }
+                };
+                format!("Invalid PKCS7 padding: {}; length={}, last 16 bytes={:?}", reason, len, last_block)
+            }
+            Error::Decode(e) => format!("Failed to decode image: {}", e),
+            Error::InvalidRowAlignment(alignment) => format!("Row alignment must be a power of two no greater than 256, got {}", alignment),
+            Error::NoCanvasContext => "Failed to get 2D context of OffscreenCanvas".to_string(),
+            Error::InvalidToken(token) => format!("Invalid result token: {}", token),
+            Error::UnsupportedLocale(lang) => format!("Unsupported locale: {}", lang),
+            Error::UnsupportedHashAlgorithm(algo) => format!("Unsupported hash algorithm: {}", algo),
+            Error::MissingIvBlock(len) => format!("Data too short for an IV block plus ciphertext: got {} bytes, need at least 32", len),
+            Error::NoGlobalScope => "Failed to get global scope (Window or Worker)".to_string(),
+            Error::SpriteIndexTruncated => "Sprite sheet index is truncated".to_string(),
+            Error::SpriteEntryOutOfBounds { index, offset, len, total } => {
+                format!("Sprite entry {} out of bounds: offset={}, length={}, total={}", index, offset, len, total)
+            }
+            Error::InsufficientCapacity { needed, offset, capacity } => {
+                format!("Target buffer too small: need {} bytes at offset {}, capacity {} bytes", needed, offset, capacity)
+            }
+            Error::InvalidLayout(segment) => format!("Invalid layout descriptor: {}", segment),
+            Error::LayoutExceedsData { needed, len } => format!("Layout exceeds data length: need {} bytes, got {} bytes", needed, len),
+            Error::Encode(e) => format!("Failed to encode image: {}", e),
+            Error::GcmTooShort(len) => format!("GCM data too short: got {} bytes, need at least 28 (12-byte nonce + 16-byte tag)", len),
+            Error::GcmAuthFailed => "GCM authentication failed: wrong key or tampered data".to_string(),
+            Error::KeyIdLength(len) => format!("Key ID must be 4 bytes, got {} bytes", len),
+            Error::MissingKeyId(len) => format!("Data too short for a 4-byte key ID plus ciphertext: got {} bytes", len),
+            Error::UnknownKeyId(kid) => format!("Unknown key ID: {}", kid),
+            Error::UnsupportedFormat(format) => format!("Unsupported target format: {}", format),
+            Error::NotDataUrl => "Not a valid data URL: expected a \"data:\" prefix and a \",\"".to_string(),
+            Error::DataUrlNotBase64 => "Data URL has no \";base64\" marker; only Base64 payloads are supported".to_string(),
+            Error::DataUrlBase64(e) => format!("Failed to decode data URL payload from Base64: {}", e),
+            Error::UnknownImageType => "Decrypted data is not a recognized image format; the key or IV may be wrong".to_string(),
+            Error::EmptySignature => "Signature must not be empty".to_string(),
+            Error::MissingCrc(len) => format!("Plaintext too short for a 4-byte CRC32: got {} bytes", len),
+            Error::CrcMismatch { expected, actual } => format!("CRC32 mismatch: expected 0x{:08x}, got 0x{:08x}", expected, actual),
+            Error::PrevBlockLength(len) => format!("Previous ciphertext block must be 16 bytes, got {} bytes", len),
+            Error::ChunkCallback => "Chunk callback threw an exception".to_string(),
+            Error::Gunzip(e) => format!("Failed to decompress gzip data: {}", e),
+            Error::MissingLengthPrefix(len) => format!("Plaintext too short for a 4-byte length prefix: got {} bytes", len),
+            Error::LengthPrefixMismatch { declared, available } => format!("Length prefix inconsistent with data: declared {} bytes, {} bytes available", declared, available),
+            Error::UnknownDimensions => "Cannot read image dimensions from the file header".to_string(),
+            Error::DimensionMismatch { expected, actual } => {
+                format!("Image dimensions mismatch: expected {}x{}, got {}x{}", expected.0, expected.1, actual.0, actual.1)
+            }
+            Error::MissingFirstBlock(len) => format!("Data too short for a full ciphertext block: got {} bytes, need at least 16", len),
+            Error::InvalidBatchItem(index) => format!("Invalid batch item at index {}: expected a Uint8Array or an object with data and iv_base64", index),
+            Error::ZeroTileSize => "Tile size must be greater than 0".to_string(),
+            Error::CbcHmacTooShort(len) => format!("Record too short: got {} bytes, need at least 64 (16-byte IV + 16-byte ciphertext + 32-byte tag)", len),
+            Error::MacMismatch => "HMAC verification failed: wrong key or tampered data".to_string(),
+            Error::GcmNonceLength(len) => format!("GCM requires a 12-byte nonce, got {} bytes (16-byte IVs are for CBC/CTR only)", len),
+            Error::GcmCiphertextTooShort(len) => format!("GCM data too short: got {} bytes, need at least a 16-byte tag", len),
+            Error::PaddingBlockSize(size) => format!("Padding block size must be 8 or 16, got {}", size),
+            Error::EnvelopeJson => "Envelope is not valid JSON".to_string(),
+            Error::MissingEnvelopeField(field) => format!("Envelope is missing string field: {}", field),
+            Error::UnsupportedCipher(alg) => format!("Unsupported cipher algorithm: {} (supported: AES-256-CBC, AES-256-GCM, AES-256-CTR)", alg),
+            Error::EnvelopeDataBase64(e) => format!("Failed to decode envelope data from Base64: {}", e),
+            Error::GunzipTooLarge(limit) => format!("Decompressed gzip data exceeds the limit of {} bytes", limit),
+            Error::PixelBufferTooLarge => "Aligned pixel buffer size exceeds the addressable range".to_string(),
+            Error::InvalidFrameBudget(budget) => format!("Frame budget must be a finite number greater than 0, got {}", budget),
+            Error::KeyIvSwapped => "Key and IV may be swapped: the key decodes to 16 bytes and the IV to 32 bytes; check the argument order".to_string(),
+        }
+    }
+}
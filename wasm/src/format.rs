@@ -0,0 +1,413 @@
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+
+use crate::error::Error;
+use crate::padding::BLOCK_SIZE;
+
+/// 读取24位小端整数（WebP等RIFF格式的字段均为小端序）
+fn read_u24_le(bytes: &[u8]) -> u32 {
+    u32::from(bytes[0]) | (u32::from(bytes[1]) << 8) | (u32::from(bytes[2]) << 16)
+}
+
+/// 解析WebP图片尺寸
+/// 支持简单有损（VP8）、简单无损（VP8L）以及扩展格式（VP8X）
+pub(crate) fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 30 || !data.starts_with(b"RIFF") || &data[8..12] != b"WEBP" {
+        return None;
+    }
+
+    match &data[12..16] {
+        // 有损：帧头3字节 + 起始码9D 01 2A，随后为14位宽高（小端，高2位为缩放系数）
+        b"VP8 " => {
+            if data[23..26] != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            let width = u32::from(u16::from_le_bytes([data[26], data[27]]) & 0x3FFF);
+            let height = u32::from(u16::from_le_bytes([data[28], data[29]]) & 0x3FFF);
+            Some((width, height))
+        }
+        // 无损：签名0x2F，随后32位小端中依次存放14位(宽-1)和14位(高-1)
+        b"VP8L" => {
+            if data[20] != 0x2F {
+                return None;
+            }
+            let bits = u32::from_le_bytes([data[21], data[22], data[23], data[24]]);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        // 扩展：标志位1字节 + 保留3字节，随后为24位小端的(画布宽-1)和(画布高-1)
+        b"VP8X" => Some((read_u24_le(&data[24..27]) + 1, read_u24_le(&data[27..30]) + 1)),
+        _ => None,
+    }
+}
+
+/// 从文件头解析的像素格式
+pub(crate) struct PixelFormat {
+    /// 每个通道的位数
+    pub(crate) bit_depth: u8,
+    pub(crate) channels: u8,
+    /// 颜色类型，如"rgb"、"rgba"、"palette"、"grayscale"
+    pub(crate) color_type: &'static str,
+}
+
+/// 解析QOI图片尺寸
+/// 文件头为"qoif"魔数，随后依次为32位大端的宽和高
+pub(crate) fn qoi_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 12 || !data.starts_with(b"qoif") {
+        return None;
+    }
+
+    let width = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+    let height = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
+    Some((width, height))
+}
+
+/// VP8X标志位中的动画位
+const VP8X_ANIMATION_FLAG: u8 = 0x02;
+
+/// WebP是否为动画：VP8X设置了动画标志位，或包含ANMF帧块
+pub(crate) fn webp_is_animated(data: &[u8]) -> bool {
+    if data.len() < 12 || !data.starts_with(b"RIFF") || &data[8..12] != b"WEBP" {
+        return false;
+    }
+
+    let mut pos = 12;
+    while let Some(header) = data.get(pos..).and_then(|rest| rest.get(..8)) {
+        let kind = &header[..4];
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+        if kind == b"ANMF" {
+            return true;
+        }
+        if kind == b"VP8X" {
+            return data.get(pos + 8).is_some_and(|flags| flags & VP8X_ANIMATION_FLAG != 0);
+        }
+
+        // 块数据按偶数字节对齐
+        match (pos + 8).checked_add(len).and_then(|end| end.checked_add(len & 1)) {
+            Some(next) => pos = next,
+            None => break,
+        }
+    }
+
+    false
+}
+
+// 计算字节熵时采样的前缀长度
+const ENTROPY_SAMPLE_SIZE: usize = 4096;
+
+// 样本太短时熵值偏低，无法可靠判断
+const ENTROPY_MIN_SAMPLE_SIZE: usize = 512;
+
+// 判定为密文的熵阈值（比特/字节），随机数据在512字节样本上约为7.6，4KB样本上约为7.95
+const CIPHERTEXT_ENTROPY_THRESHOLD: f64 = 7.5;
+
+/// 计算数据前缀的香农熵（比特/字节，取值0~8）
+pub(crate) fn byte_entropy(data: &[u8]) -> f64 {
+    let sample = &data[..data.len().min(ENTROPY_SAMPLE_SIZE)];
+    if sample.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in sample {
+        counts[byte as usize] += 1;
+    }
+
+    let total = sample.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// 判断数据是否像未解密的密文
+/// 仅依据熵值，调用方应先确认数据没有可识别的文件头
+pub(crate) fn looks_like_ciphertext(data: &[u8]) -> bool {
+    data.len() >= ENTROPY_MIN_SAMPLE_SIZE && byte_entropy(data) >= CIPHERTEXT_ENTROPY_THRESHOLD
+}
+
+// 检测Lottie时扫描的前缀长度
+const LOTTIE_SCAN_SIZE: usize = 1024;
+
+// Lottie顶层必有的字段：版本、帧率、起止帧、宽高和图层
+const LOTTIE_KEYS: [&[u8]; 7] = [b"\"v\"", b"\"fr\"", b"\"ip\"", b"\"op\"", b"\"w\"", b"\"h\"", b"\"layers\""];
+
+// 判定为Lottie至少需要出现的字段数
+const LOTTIE_MIN_KEYS: usize = 4;
+
+/// 判断数据是否像Lottie动画JSON
+/// 只扫描前1KB：以`{`开头（允许前导空白），且出现足够多的Lottie顶层字段，不做完整的JSON解析
+pub(crate) fn looks_like_lottie(data: &[u8]) -> bool {
+    let prefix = &data[..data.len().min(LOTTIE_SCAN_SIZE)];
+    if prefix.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'{') {
+        return false;
+    }
+
+    let found = LOTTIE_KEYS
+        .iter()
+        .filter(|key| prefix.windows(key.len()).any(|window| window == **key))
+        .count();
+    found >= LOTTIE_MIN_KEYS
+}
+
+/// 规范化Content-Type：去掉参数（如`; charset=...`）和首尾空白并转为小写，
+/// 常见的非标准写法`image/jpg`视为`image/jpeg`
+pub(crate) fn normalize_mime(mime: &str) -> String {
+    let essence = mime.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    match essence.as_str() {
+        "image/jpg" | "image/pjpeg" => "image/jpeg".to_string(),
+        _ => essence,
+    }
+}
+
+/// 文本检测扫描的前缀长度（字节）
+pub(crate) const TEXT_SCAN_SIZE: usize = 4096;
+
+/// 判断数据是否像文本（如SVG、JSON）
+/// 只扫描前4KB：必须是合法的UTF-8（末尾被截断的多字节字符不算错误），
+/// 且除制表符、换行、回车和换页外不含控制字符。空数据视为二进制
+pub(crate) fn looks_like_text(data: &[u8]) -> bool {
+    if data.is_empty() {
+        return false;
+    }
+
+    let prefix = &data[..data.len().min(TEXT_SCAN_SIZE)];
+    let valid = match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && prefix.len() == TEXT_SCAN_SIZE,
+    };
+
+    valid
+        && prefix
+            .iter()
+            .all(|&byte| !byte.is_ascii_control() || matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c))
+}
+
+/// 解析精灵图集的索引表
+/// 布局：4字节大端图片数量，随后每张图片依次为4字节大端偏移和4字节大端长度，
+/// 偏移从明文起始位置计算。返回每张图片的(偏移, 长度)，并保证都不越界
+pub(crate) fn sprite_entries(data: &[u8]) -> Result<Vec<(usize, usize)>, Error> {
+    let count = data
+        .get(..4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+        .ok_or(Error::SpriteIndexTruncated)?;
+
+    let index_end = count
+        .checked_mul(8)
+        .and_then(|len| len.checked_add(4))
+        .filter(|&end| end <= data.len())
+        .ok_or(Error::SpriteIndexTruncated)?;
+
+    data[4..index_end]
+        .chunks_exact(8)
+        .enumerate()
+        .map(|(index, entry)| {
+            let offset = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]) as usize;
+            let len = u32::from_be_bytes([entry[4], entry[5], entry[6], entry[7]]) as usize;
+            match offset.checked_add(len) {
+                Some(end) if end <= data.len() => Ok((offset, len)),
+                _ => Err(Error::SpriteEntryOutOfBounds { index, offset, len, total: data.len() }),
+            }
+        })
+        .collect()
+}
+
+/// 布局描述中的一个字段
+pub(crate) struct LayoutField {
+    pub(crate) name: String,
+    /// 字段长度，None表示占用剩余全部字节（`*`）
+    pub(crate) len: Option<usize>,
+}
+
+/// 解析形如"key:32,iv:16,ct:*"的布局描述
+/// 字段名不可重复，`*`只能用于最后一个字段
+pub(crate) fn parse_layout(layout: &str) -> Result<Vec<LayoutField>, Error> {
+    let segments: Vec<&str> = layout.split(',').map(str::trim).collect();
+    let mut fields: Vec<LayoutField> = Vec::with_capacity(segments.len());
+
+    for (index, segment) in segments.iter().enumerate() {
+        let invalid = || Error::InvalidLayout(segment.to_string());
+        let (name, len) = segment.split_once(':').ok_or_else(invalid)?;
+        let (name, len) = (name.trim(), len.trim());
+
+        if name.is_empty() || fields.iter().any(|field| field.name == name) {
+            return Err(invalid());
+        }
+
+        let len = match len {
+            "*" if index == segments.len() - 1 => None,
+            _ => Some(len.parse::<usize>().map_err(|_| invalid())?),
+        };
+
+        fields.push(LayoutField { name: name.to_string(), len });
+    }
+
+    Ok(fields)
+}
+
+/// 解析Base64形式的data URL并返回其中尚未解码的Base64数据
+/// 格式为`data:[<MIME类型>][;参数]*;base64,<数据>`，MIME类型可省略（如`data:;base64,...`）
+pub(crate) fn data_url_payload(url: &str) -> Result<&str, Error> {
+    let url = url.trim();
+    let rest = url
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .map(|_| &url[5..])
+        .ok_or(Error::NotDataUrl)?;
+
+    let (header, payload) = rest.split_once(',').ok_or(Error::NotDataUrl)?;
+    // base64标记必须是以";"分隔的最后一个参数；RFC 2397中没有";"的"data:base64,"表示MIME类型为"base64"
+    let is_base64 = header
+        .rsplit_once(';')
+        .is_some_and(|(_, param)| param.trim().eq_ignore_ascii_case("base64"));
+    if !is_base64 {
+        return Err(Error::DataUrlNotBase64);
+    }
+
+    Ok(payload.trim())
+}
+
+/// 校验并去掉末尾4字节大端CRC32，返回其前的数据
+pub(crate) fn strip_crc32(data: &[u8]) -> Result<&[u8], Error> {
+    if data.len() < 4 {
+        return Err(Error::MissingCrc(data.len()));
+    }
+
+    let (body, trailer) = data.split_at(data.len() - 4);
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let actual = crc32fast::hash(body);
+    if actual != expected {
+        return Err(Error::CrcMismatch { expected, actual });
+    }
+
+    Ok(body)
+}
+
+/// 解析缺少前导零的十六进制IV（30或31位十六进制数字），左侧补零后返回16字节。
+/// 只修复丢失的前导零，其他长度（包括完整的32位）或含非十六进制字符时返回None
+pub(crate) fn decode_short_hex_iv(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim();
+    if !(30..=31).contains(&hex.len()) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let padded = format!("{:0>32}", hex);
+    (0..32)
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&padded[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// 解压gzip数据，解压结果超过`limit`字节时返回错误，防止解压炸弹耗尽内存
+pub(crate) fn gunzip(data: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    GzDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(|e| Error::Gunzip(e.to_string()))?;
+
+    if output.len() > limit {
+        return Err(Error::GunzipTooLarge(limit));
+    }
+    Ok(output)
+}
+
+/// 在分隔符第一次出现处拆分数据，返回分隔符之前和之后的部分（均不含分隔符）
+/// 找不到分隔符或分隔符为空时，头部为空，全部数据作为正文
+pub(crate) fn split_at_delimiter<'a>(data: &'a [u8], delimiter: &[u8]) -> (&'a [u8], &'a [u8]) {
+    if delimiter.is_empty() {
+        return (&[], data);
+    }
+
+    match data.windows(delimiter.len()).position(|window| window == delimiter) {
+        Some(pos) => (&data[..pos], &data[pos + delimiter.len()..]),
+        None => (&[], data),
+    }
+}
+
+/// 按明文开头的4字节大端长度截取数据
+/// 长度之后的字节数必须不少于声明的长度，且多出的填充字节不超过一个分组
+pub(crate) fn length_prefixed(data: &[u8]) -> Result<&[u8], Error> {
+    let [a, b, c, d, rest @ ..] = data else {
+        return Err(Error::MissingLengthPrefix(data.len()));
+    };
+
+    let declared = u32::from_be_bytes([*a, *b, *c, *d]) as usize;
+    if declared > rest.len() || rest.len() - declared > BLOCK_SIZE {
+        return Err(Error::LengthPrefixMismatch { declared, available: rest.len() });
+    }
+
+    Ok(&rest[..declared])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 640×480的VP8X扩展格式文件头（带Alpha标志），画布宽高按减1后的24位小端存放
+    const VP8X_HEADER: [u8; 30] = [
+        b'R', b'I', b'F', b'F', 0x24, 0x00, 0x00, 0x00, b'W', b'E', b'B', b'P',
+        b'V', b'P', b'8', b'X', 0x0A, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+        0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00,
+    ];
+
+    #[test]
+    fn webp_dimensions_reads_vp8x_canvas_size() {
+        assert_eq!(webp_dimensions(&VP8X_HEADER), Some((640, 480)));
+    }
+
+    #[test]
+    fn webp_dimensions_reads_maximum_vp8x_canvas_size() {
+        let mut header = VP8X_HEADER;
+        header[24..30].copy_from_slice(&[0xFF; 6]);
+        assert_eq!(webp_dimensions(&header), Some((1 << 24, 1 << 24)));
+    }
+
+    #[test]
+    fn webp_dimensions_rejects_truncated_header() {
+        assert_eq!(webp_dimensions(&VP8X_HEADER[..29]), None);
+    }
+
+    #[test]
+    fn webp_dimensions_rejects_non_webp_riff() {
+        let mut header = VP8X_HEADER;
+        header[8..12].copy_from_slice(b"WAVE");
+        assert_eq!(webp_dimensions(&header), None);
+    }
+
+    #[test]
+    fn short_hex_iv_pads_dropped_leading_zeros() {
+        let expected: Vec<u8> = [vec![0x00], (1..16).collect()].concat();
+        assert_eq!(decode_short_hex_iv("0102030405060708090a0b0c0d0e0f").as_deref(), Some(&expected[..]));
+
+        let expected: Vec<u8> = [vec![0x01], (1..16).collect()].concat();
+        assert_eq!(decode_short_hex_iv(" 10102030405060708090a0b0c0d0e0f\n").as_deref(), Some(&expected[..]));
+    }
+
+    #[test]
+    fn short_hex_iv_rejects_other_lengths_and_non_hex() {
+        assert_eq!(decode_short_hex_iv(&"0".repeat(29)), None);
+        assert_eq!(decode_short_hex_iv("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"), None);
+        assert_eq!(decode_short_hex_iv("g0f1f2f3f4f5f6f7f8f9fafbfcfdfef"), None);
+    }
+
+    #[test]
+    fn data_url_payload_requires_base64_parameter() {
+        assert_eq!(data_url_payload("data:image/png;base64,AAAA").ok(), Some("AAAA"));
+        assert_eq!(data_url_payload(" DATA:;BASE64, AAAA \n").ok(), Some("AAAA"));
+        assert_eq!(data_url_payload("data:application/octet-stream;name=a.bin;base64,AAAA").ok(), Some("AAAA"));
+    }
+
+    #[test]
+    fn data_url_payload_rejects_base64_as_media_type() {
+        assert!(matches!(data_url_payload("data:base64,AAAA"), Err(Error::DataUrlNotBase64)));
+        assert!(matches!(data_url_payload("data:image/png,AAAA"), Err(Error::DataUrlNotBase64)));
+        assert!(matches!(data_url_payload("data:;base64;charset=utf-8,AAAA"), Err(Error::DataUrlNotBase64)));
+        assert!(matches!(data_url_payload("blob:AAAA"), Err(Error::NotDataUrl)));
+    }
+}
@@ -0,0 +1,41 @@
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+
+use crate::error::Error;
+
+/// GCM随机数长度（字节）
+pub(crate) const NONCE_SIZE: usize = 12;
+/// GCM认证标签长度（字节）
+pub(crate) const TAG_SIZE: usize = 16;
+
+/// 解密`nonce(12) || 密文 || tag(16)`布局的AES-256-GCM数据
+/// 认证标签校验失败时不返回任何明文
+pub(crate) fn decrypt_combined(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(Error::GcmTooShort(data.len()));
+    }
+
+    let (nonce, rest) = data.split_at(NONCE_SIZE);
+    decrypt_detached(key, nonce, rest)
+}
+
+/// 使用单独传入的nonce解密`密文 || tag(16)`布局的AES-256-GCM数据
+/// nonce必须恰好为12字节；16字节的CBC IV不会被截断或扩展，而是直接报错
+pub(crate) fn decrypt_detached(key: &[u8], nonce: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    let nonce: [u8; NONCE_SIZE] = nonce.try_into().map_err(|_| Error::GcmNonceLength(nonce.len()))?;
+
+    if data.len() < TAG_SIZE {
+        return Err(Error::GcmCiphertextTooShort(data.len()));
+    }
+
+    let (ciphertext, tag) = data.split_at(data.len() - TAG_SIZE);
+    let tag: [u8; TAG_SIZE] = tag.try_into().expect("tag长度已校验");
+
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| Error::CipherInit(e.to_string()))?;
+    let mut buffer = ciphertext.to_vec();
+    cipher
+        .decrypt_in_place_detached(&Nonce::from(nonce), &[], &mut buffer, &Tag::from(tag))
+        .map_err(|_| Error::GcmAuthFailed)?;
+
+    Ok(buffer)
+}
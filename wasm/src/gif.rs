@@ -0,0 +1,77 @@
+/// 图像描述符
+const IMAGE_DESCRIPTOR: u8 = 0x2C;
+/// 扩展块引导符
+const EXTENSION: u8 = 0x21;
+/// 文件结束符
+pub(crate) const TRAILER: u8 = 0x3B;
+
+/// 解析逻辑屏幕描述符中的画布尺寸（16位小端）
+pub(crate) fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return None;
+    }
+
+    let screen = data.get(6..10)?;
+    let width = u32::from(u16::from_le_bytes([screen[0], screen[1]]));
+    let height = u32::from(u16::from_le_bytes([screen[2], screen[3]]));
+    Some((width, height))
+}
+
+/// 统计GIF中的图像描述符数量，最多数到`limit`
+/// 数据截断或格式错误时返回此前已统计到的数量；不是GIF时返回None
+pub(crate) fn frame_count(data: &[u8], limit: usize) -> Option<usize> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return None;
+    }
+
+    // 文件头(6) + 逻辑屏幕描述符(7)，随后为可选的全局颜色表
+    let flags = *data.get(10)?;
+    let mut pos = 13 + color_table_size(flags);
+    let mut frames = 0;
+
+    while frames < limit {
+        match data.get(pos) {
+            Some(&IMAGE_DESCRIPTOR) => {
+                frames += 1;
+                // 描述符(10) + 可选的局部颜色表 + LZW最小码长(1) + 数据子块
+                let Some(&flags) = data.get(pos.saturating_add(9)) else { break };
+                let Some(next) = pos
+                    .checked_add(10 + color_table_size(flags) + 1)
+                    .and_then(|start| skip_sub_blocks(data, start))
+                else {
+                    break;
+                };
+                pos = next;
+            }
+            // 引导符(1) + 标签(1) + 数据子块
+            Some(&EXTENSION) => {
+                let Some(next) = pos.checked_add(2).and_then(|start| skip_sub_blocks(data, start)) else { break };
+                pos = next;
+            }
+            Some(&TRAILER) | Some(_) | None => break,
+        }
+    }
+
+    Some(frames)
+}
+
+/// 颜色表字节数，标志位最高位表示是否存在，低3位为大小指数
+fn color_table_size(flags: u8) -> usize {
+    if flags & 0x80 == 0 {
+        0
+    } else {
+        3 << ((flags & 0x07) + 1)
+    }
+}
+
+/// 跳过以长度0结尾的数据子块序列，返回其后的位置；数据截断或位置溢出时返回None
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *data.get(pos)? as usize;
+        pos = pos.checked_add(1)?;
+        if len == 0 {
+            return Some(pos);
+        }
+        pos = pos.checked_add(len)?;
+    }
+}
@@ -0,0 +1,94 @@
+use js_sys::Uint8Array;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Locale};
+
+/// 增量哈希计算器
+/// 可在密文分块到达时逐块计算校验值，无需缓存完整文件
+#[wasm_bindgen]
+pub struct IncrementalHasher {
+    state: HasherState,
+}
+
+enum HasherState {
+    Crc32(crc32fast::Hasher),
+    Sha256(Sha256),
+}
+
+#[wasm_bindgen]
+impl IncrementalHasher {
+    /// 创建新的增量哈希计算器
+    /// 
+    /// # 参数
+    /// - `algo`: 哈希算法，支持"crc32"和"sha256"（不区分大小写，也接受"sha-256"）
+    #[wasm_bindgen(constructor)]
+    pub fn new(algo: &str) -> Result<IncrementalHasher, JsValue> {
+        let state = match algo.to_ascii_lowercase().as_str() {
+            "crc32" => HasherState::Crc32(crc32fast::Hasher::new()),
+            "sha256" | "sha-256" => HasherState::Sha256(Sha256::new()),
+            _ => return Err(Error::UnsupportedHashAlgorithm(algo.to_string()).to_js(Locale::default())),
+        };
+
+        Ok(IncrementalHasher { state })
+    }
+
+    /// 追加一个数据分块
+    #[wasm_bindgen]
+    pub fn update(&mut self, chunk: &Uint8Array) {
+        let bytes = chunk.to_vec();
+        match &mut self.state {
+            HasherState::Crc32(hasher) => hasher.update(&bytes),
+            HasherState::Sha256(hasher) => hasher.update(&bytes),
+        }
+    }
+
+    /// 结束计算并返回十六进制摘要
+    /// 调用后该实例不可再使用
+    #[wasm_bindgen]
+    pub fn finalize(self) -> String {
+        match self.state {
+            HasherState::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+            HasherState::Sha256(hasher) => to_hex(&hasher.finalize()),
+        }
+    }
+}
+
+/// 缓存键取SHA-256摘要的前8字节（16位十六进制）
+const CACHE_KEY_BYTES: usize = 8;
+
+/// 生成缓存键：SHA-256摘要前8字节的十六进制
+pub(crate) fn cache_key(hasher: Sha256) -> String {
+    to_hex(&hasher.finalize()[..CACHE_KEY_BYTES])
+}
+
+/// SHA-256的分组长度（字节），HMAC按此长度填充密钥
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// 计算HMAC-SHA256（RFC 2104）
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    hmac_sha256_parts(key, &[message])
+}
+
+/// 计算多段拼接消息的HMAC-SHA256，无需先拼接成一个缓冲区
+pub(crate) fn hmac_sha256_parts(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut block_key = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let pad = |byte: u8| block_key.map(|k| k ^ byte);
+    let mut inner = Sha256::new().chain_update(pad(0x36));
+    for part in parts {
+        inner.update(part);
+    }
+    let inner = inner.finalize();
+    Sha256::new().chain_update(pad(0x5C)).chain_update(inner).finalize().into()
+}
+
+/// 将字节转换为小写十六进制字符串
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
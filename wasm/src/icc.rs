@@ -0,0 +1,172 @@
+/// ICC配置文件头部长度，标签表紧随其后
+const HEADER_SIZE: usize = 128;
+
+/// 标签表中单个条目的字节数：签名(4) + 偏移(4) + 长度(4)
+const TAG_ENTRY_SIZE: usize = 12;
+
+/// 读取32位大端整数
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+    Some(u32::from_be_bytes(bytes))
+}
+
+/// 查找指定签名的标签数据
+fn tag<'a>(profile: &'a [u8], signature: &[u8; 4]) -> Option<&'a [u8]> {
+    let count = read_u32(profile, HEADER_SIZE)? as usize;
+    let table = profile.get(HEADER_SIZE + 4..)?;
+
+    // 条目数来自文件内容，只遍历标签表实际包含的条目，避免伪造的数量导致长时间循环
+    table.chunks_exact(TAG_ENTRY_SIZE).take(count).find_map(|entry| {
+        if &entry[..4] != signature {
+            return None;
+        }
+        let offset = read_u32(entry, 4)? as usize;
+        let len = read_u32(entry, 8)? as usize;
+        profile.get(offset..offset.checked_add(len)?)
+    })
+}
+
+/// 读取配置文件描述（desc标签）
+/// 支持ICC v2的textDescriptionType（ASCII）和v4的multiLocalizedUnicodeType（取第一条UTF-16BE记录）
+pub(crate) fn description(profile: &[u8]) -> Option<String> {
+    let desc = tag(profile, b"desc")?;
+
+    match desc.get(..4)? {
+        // 类型(4) + 保留(4) + ASCII长度(4，含结尾NUL) + 字符串
+        b"desc" => {
+            let len = read_u32(desc, 8)? as usize;
+            let text = desc.get(12..len.checked_add(12)?)?;
+            Some(String::from_utf8_lossy(text).trim_end_matches('\0').to_string())
+        }
+        // 类型(4) + 保留(4) + 记录数(4) + 记录长度(4)，每条记录为语言(2) + 地区(2) + 长度(4) + 偏移(4)
+        b"mluc" => {
+            let len = read_u32(desc, 20)? as usize;
+            let offset = read_u32(desc, 24)? as usize;
+            let units: Vec<u16> = desc
+                .get(offset..offset.checked_add(len)?)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None,
+    }
+}
+
+/// Display P3经Bradford变换适配到D50后的红、绿、蓝原色XYZ值（与Apple、Android的Display P3配置文件一致）
+const DISPLAY_P3_COLORANTS: [[f64; 3]; 3] = [
+    [0.5151, 0.2412, -0.0011],
+    [0.2920, 0.6922, 0.0419],
+    [0.1571, 0.0666, 0.7841],
+];
+
+/// 原色XYZ值允许的误差，吸收各厂商s15Fixed16取整和适配矩阵的细微差异
+const COLORANT_TOLERANCE: f64 = 0.005;
+
+/// 没有原色标签时按描述识别的已知P3配置文件名称，必须完全一致
+const P3_DESCRIPTIONS: [&str; 1] = ["Display P3"];
+
+/// 读取XYZType标签（类型"XYZ "(4) + 保留(4) + s15Fixed16的X、Y、Z各4字节）
+fn xyz(profile: &[u8], signature: &[u8; 4]) -> Option<[f64; 3]> {
+    let data = tag(profile, signature)?;
+    if data.get(..4)? != b"XYZ " {
+        return None;
+    }
+
+    let component = |offset| read_u32(data, offset).map(|value| f64::from(value as i32) / 65536.0);
+    Some([component(8)?, component(12)?, component(16)?])
+}
+
+/// 启发式判断配置文件是否为Display P3
+/// 优先比较rXYZ、gXYZ、bXYZ原色与Display P3的D50适配值，三者都在误差范围内时视为P3；
+/// 缺少原色标签（如基于LUT的配置文件）时退回按描述完全匹配已知名称。
+/// 原色相同但白点不同的DCI-P3等变体不会被识别
+pub(crate) fn is_p3(profile: &[u8]) -> bool {
+    let colorants = [b"rXYZ", b"gXYZ", b"bXYZ"].map(|signature| xyz(profile, signature));
+    if colorants.iter().all(Option::is_some) {
+        return colorants.iter().flatten().zip(DISPLAY_P3_COLORANTS).all(|(actual, expected)| {
+            actual.iter().zip(expected).all(|(a, e)| (a - e).abs() <= COLORANT_TOLERANCE)
+        });
+    }
+
+    description(profile).is_some_and(|desc| P3_DESCRIPTIONS.contains(&desc.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造只含指定标签的配置文件，头部全部填零
+    fn profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_SIZE];
+        data.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+
+        let mut offset = HEADER_SIZE + 4 + tags.len() * TAG_ENTRY_SIZE;
+        for (signature, body) in tags {
+            data.extend_from_slice(*signature);
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            offset += body.len();
+        }
+        for (_, body) in tags {
+            data.extend_from_slice(body);
+        }
+        data
+    }
+
+    fn xyz_tag(values: [f64; 3]) -> Vec<u8> {
+        let mut body = b"XYZ \0\0\0\0".to_vec();
+        for value in values {
+            body.extend_from_slice(&((value * 65536.0).round() as i32).to_be_bytes());
+        }
+        body
+    }
+
+    fn desc_tag(text: &str) -> Vec<u8> {
+        let mut body = b"desc\0\0\0\0".to_vec();
+        body.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+        body.extend_from_slice(text.as_bytes());
+        body.push(0);
+        body
+    }
+
+    fn colorant_tags(colorants: [[f64; 3]; 3], desc: &str) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        vec![
+            (b"desc", desc_tag(desc)),
+            (b"rXYZ", xyz_tag(colorants[0])),
+            (b"gXYZ", xyz_tag(colorants[1])),
+            (b"bXYZ", xyz_tag(colorants[2])),
+        ]
+    }
+
+    /// sRGB IEC61966-2.1的D50适配原色
+    const SRGB_COLORANTS: [[f64; 3]; 3] = [
+        [0.4361, 0.2225, 0.0139],
+        [0.3851, 0.7169, 0.0971],
+        [0.1431, 0.0606, 0.7141],
+    ];
+
+    #[test]
+    fn identifies_p3_by_primaries() {
+        assert!(is_p3(&profile(&colorant_tags(DISPLAY_P3_COLORANTS, "Custom display"))));
+    }
+
+    #[test]
+    fn primaries_take_precedence_over_description() {
+        assert!(!is_p3(&profile(&colorant_tags(SRGB_COLORANTS, "Display P3"))));
+    }
+
+    #[test]
+    fn falls_back_to_exact_description_without_primaries() {
+        assert!(is_p3(&profile(&[(b"desc", desc_tag("Display P3"))])));
+        assert!(!is_p3(&profile(&[(b"desc", desc_tag("Not Display P3 compatible"))])));
+        assert!(!is_p3(&profile(&[(b"desc", desc_tag("sRGB IEC61966-2.1"))])));
+    }
+
+    #[test]
+    fn reads_signed_xyz_components() {
+        let data = profile(&[(b"rXYZ", xyz_tag([0.5151, 0.2412, -0.0011]))]);
+        let [x, y, z] = xyz(&data, b"rXYZ").unwrap();
+        assert!((x - 0.5151).abs() < 1e-4 && (y - 0.2412).abs() < 1e-4 && (z + 0.0011).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,265 @@
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use js_sys::Uint8Array;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Locale};
+use crate::padding::{self, BLOCK_SIZE};
+use crate::{Aes256CbcDec, ImageDecryptor};
+
+/// 增量CBC解密器
+/// 用于解密整体作为一条连续CBC流加密的多段数据：每次update都沿用上一段最后一个
+/// 密文块作为链接值，而不是重新使用初始IV。只在finalize时校验并移除末尾的PKCS7填充
+#[wasm_bindgen]
+pub struct IncrementalDecryptor {
+    key: Vec<u8>,
+    // 下一个密文块的链接值：初始为IV，之后为上一个已解密的密文块
+    chain: Vec<u8>,
+    // 尚未解密的密文：不足一块的尾部，以及为finalize保留的最后一个完整块
+    pending: Vec<u8>,
+    received: usize,
+    // PKCS7填充的分组长度，默认16，兼容按8字节分组填充的旧工具时为8
+    pad_block_size: usize,
+    // 是否在finish时去掉明文末尾的一个换行符
+    trim_trailing_newline: bool,
+    // 开启去换行时暂缓输出的上一段明文最后一个字节：
+    // `\r\n`可能跨越末块边界，`\r`要等到finish才能确定是否属于末尾换行符
+    held_back: Option<u8>,
+}
+
+#[wasm_bindgen]
+impl IncrementalDecryptor {
+    /// 创建增量解密器
+    /// 
+    /// # 参数
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量，仅用于第一个密文块
+    #[wasm_bindgen(constructor)]
+    pub fn new(key_base64: &str, iv_base64: &str) -> Result<IncrementalDecryptor, JsValue> {
+        Self::create(key_base64, iv_base64).map_err(|e| e.to_js(Locale::default()))
+    }
+
+    /// 追加一段密文并返回目前可以确定的明文
+    /// 分段无需按16字节对齐；最后一个完整块会保留到下次调用或finalize，
+    /// 因此返回的明文可能比本段密文少最多16字节
+    #[wasm_bindgen]
+    pub fn update(&mut self, chunk: &Uint8Array) -> Result<Uint8Array, JsValue> {
+        self.feed(&chunk.to_vec())
+            .map(|plaintext| Uint8Array::from(&plaintext[..]))
+            .map_err(|e| e.to_js(Locale::default()))
+    }
+
+    /// 结束解密，解密保留的最后一块并移除PKCS7填充
+    /// 调用后该实例不可再使用
+    #[wasm_bindgen]
+    pub fn finalize(self) -> Result<Uint8Array, JsValue> {
+        self.finish()
+            .map(|tail| Uint8Array::from(&tail[..]))
+            .map_err(|e| e.to_js(Locale::default()))
+    }
+}
+
+impl IncrementalDecryptor {
+    /// 解码密钥和IV并创建解密器
+    pub(crate) fn create(key_base64: &str, iv_base64: &str) -> Result<IncrementalDecryptor, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if iv_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        let key = ImageDecryptor::decode_key(key_base64)?;
+        let iv = ImageDecryptor::decode_iv(iv_base64)?;
+
+        Ok(Self::from_parts(key, iv))
+    }
+
+    /// 用已解码的32字节密钥和16字节IV创建解密器
+    pub(crate) fn from_parts(key: Vec<u8>, iv: Vec<u8>) -> IncrementalDecryptor {
+        IncrementalDecryptor {
+            key,
+            chain: iv,
+            pending: Vec::new(),
+            received: 0,
+            pad_block_size: BLOCK_SIZE,
+            trim_trailing_newline: false,
+            held_back: None,
+        }
+    }
+
+    /// 设置finish时移除PKCS7填充所用的分组长度，调用方须已用`padding::check_block_size`校验
+    pub(crate) fn with_pad_block_size(mut self, pad_block_size: usize) -> IncrementalDecryptor {
+        self.pad_block_size = pad_block_size;
+        self
+    }
+
+    /// 设置finish时是否去掉明文末尾的一个换行符（`\n`或`\r\n`）
+    pub(crate) fn with_trim_trailing_newline(mut self, trim: bool) -> IncrementalDecryptor {
+        self.trim_trailing_newline = trim;
+        self
+    }
+
+    /// 追加密文并解密除最后一个完整块以外的所有完整块
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        self.received += chunk.len();
+        self.pending.extend_from_slice(chunk);
+
+        // 至少保留1~16字节，保证最后一个完整块留给finalize移除填充
+        let ready = self.pending.len().saturating_sub(1) / BLOCK_SIZE * BLOCK_SIZE;
+        let mut plaintext: Vec<u8> = self.pending.drain(..ready).collect();
+        self.decrypt_blocks(&mut plaintext)?;
+
+        if self.trim_trailing_newline && !plaintext.is_empty() {
+            if let Some(byte) = self.held_back.take() {
+                plaintext.insert(0, byte);
+            }
+            self.held_back = plaintext.pop();
+        }
+
+        Ok(plaintext)
+    }
+
+    /// 解密保留的最后一块并移除填充
+    pub(crate) fn finish(mut self) -> Result<Vec<u8>, Error> {
+        if self.received == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        if self.pending.len() != BLOCK_SIZE {
+            return Err(Error::UnalignedData);
+        }
+
+        let mut tail = std::mem::take(&mut self.pending);
+        let last_block = tail.clone();
+        self.decrypt_blocks(&mut tail)?;
+
+        let len = padding::pkcs7_unpadded_len_for(&tail, self.pad_block_size).map_err(|fault| Error::Padding {
+            fault,
+            len: self.received,
+            last_block,
+        })?;
+        tail.truncate(len);
+
+        if self.trim_trailing_newline {
+            if let Some(byte) = self.held_back.take() {
+                tail.insert(0, byte);
+            }
+            tail.truncate(tail.len() - padding::trailing_newline_len(&tail));
+        }
+
+        Ok(tail)
+    }
+
+    /// 原地解密若干完整块，并把最后一个密文块记为下一次的链接值
+    fn decrypt_blocks(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let next_chain = data[data.len() - BLOCK_SIZE..].to_vec();
+        Aes256CbcDec::new_from_slices(&self.key, &self.chain)
+            .map_err(|e| Error::CipherInit(e.to_string()))?
+            .decrypt_padded_mut::<NoPadding>(data)
+            .map_err(|e| Error::CipherInit(e.to_string()))?;
+        self.chain = next_chain;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes256;
+    use cbc::cipher::block_padding::Pkcs7;
+    use cbc::cipher::BlockEncryptMut;
+
+    const KEY: [u8; 32] = [0x2B; 32];
+    const IV: [u8; 16] = [0x0F; 16];
+
+    fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+        let mut buffer = plaintext.to_vec();
+        buffer.resize(plaintext.len() + BLOCK_SIZE, 0);
+        let len = cbc::Encryptor::<Aes256>::new_from_slices(&KEY, &IV)
+            .unwrap()
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .unwrap()
+            .len();
+        buffer.truncate(len);
+        buffer
+    }
+
+    /// 一次性解密并移除填充，作为分段解密的对照
+    fn decrypt_one_shot(ciphertext: &[u8]) -> Vec<u8> {
+        let mut buffer = ciphertext.to_vec();
+        Aes256CbcDec::new_from_slices(&KEY, &IV)
+            .unwrap()
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .unwrap()
+            .to_vec()
+    }
+
+    fn decrypt_in_chunks(ciphertext: &[u8], chunk_size: usize) -> Result<Vec<u8>, Error> {
+        let mut decryptor = IncrementalDecryptor::from_parts(KEY.to_vec(), IV.to_vec());
+        let mut plaintext = Vec::new();
+        for chunk in ciphertext.chunks(chunk_size) {
+            plaintext.extend(decryptor.feed(chunk)?);
+        }
+        plaintext.extend(decryptor.finish()?);
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn chunked_output_matches_one_shot_decryption() {
+        for plaintext_len in [0, 1, 15, 16, 17, 31, 32, 100] {
+            let plaintext: Vec<u8> = (0..plaintext_len as u8).collect();
+            let ciphertext = encrypt(&plaintext);
+            let expected = decrypt_one_shot(&ciphertext);
+            assert_eq!(expected, plaintext);
+
+            for chunk_size in [1, 15, 16, 17, ciphertext.len()] {
+                assert_eq!(
+                    decrypt_in_chunks(&ciphertext, chunk_size).unwrap(),
+                    expected,
+                    "plaintext {} bytes, chunks of {}",
+                    plaintext_len,
+                    chunk_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn holds_back_last_block_until_finish() {
+        let ciphertext = encrypt(&[0x55; 40]);
+        let mut decryptor = IncrementalDecryptor::from_parts(KEY.to_vec(), IV.to_vec());
+
+        assert!(decryptor.feed(&ciphertext[..16]).unwrap().is_empty());
+        assert_eq!(decryptor.feed(&ciphertext[16..]).unwrap().len(), 32);
+        assert_eq!(decryptor.finish().unwrap(), vec![0x55; 8]);
+    }
+
+    #[test]
+    fn rejects_empty_and_unaligned_input() {
+        let decryptor = IncrementalDecryptor::from_parts(KEY.to_vec(), IV.to_vec());
+        assert!(matches!(decryptor.finish(), Err(Error::EmptyData)));
+
+        let ciphertext = encrypt(&[0x33; 20]);
+        assert!(matches!(decrypt_in_chunks(&ciphertext[..15], 4), Err(Error::UnalignedData)));
+        assert!(matches!(decrypt_in_chunks(&ciphertext[..17], 4), Err(Error::UnalignedData)));
+    }
+
+    #[test]
+    fn rejects_bad_padding() {
+        // 20字节明文的末块为4字节数据加12个0x0C；前一密文块按位异或到末块明文上，
+        // 把最后一个填充字节改为17
+        let mut ciphertext = encrypt(&[0x33; 20]);
+        ciphertext[BLOCK_SIZE - 1] ^= 0x0C ^ 17;
+        assert!(matches!(
+            decrypt_in_chunks(&ciphertext, 16),
+            Err(Error::Padding { fault: padding::PaddingFault::Length { pad: 17, .. }, .. })
+        ));
+    }
+}
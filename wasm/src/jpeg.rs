@@ -0,0 +1,342 @@
+/// 渐进式帧起始标记（SOF2）
+pub(crate) const SOF2: u8 = 0xC2;
+/// 扫描起始标记
+pub(crate) const SOS: u8 = 0xDA;
+/// 图像结束标记
+pub(crate) const EOI: u8 = 0xD9;
+/// 定义重启间隔标记，负载固定为2字节
+pub(crate) const DRI: u8 = 0xDD;
+/// APP2标记，ICC配置文件存放于此
+pub(crate) const APP2: u8 = 0xE2;
+
+/// APP2中ICC配置文件的标识
+pub(crate) const ICC_PROFILE_ID: &[u8] = b"ICC_PROFILE\0";
+
+/// 一个JPEG标记段
+pub(crate) struct Segment<'a> {
+    pub(crate) marker: u8,
+    /// 长度字段之后的数据，无长度字段的标记为空
+    pub(crate) payload: &'a [u8],
+}
+
+/// 按顺序遍历SOI之后的标记段，遇到SOS或EOI后停止
+/// 数据截断或格式错误时产生`Err(偏移)`并停止
+pub(crate) struct Segments<'a> {
+    data: &'a [u8],
+    pos: usize,
+    done: bool,
+}
+
+/// 遍历JPEG标记段，数据不以SOI开头时返回None
+pub(crate) fn segments(data: &[u8]) -> Option<Segments<'_>> {
+    if data.len() < 2 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+
+    Some(Segments { data, pos: 2, done: false })
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Result<Segment<'a>, usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let offset = self.pos;
+        let result = self.read_segment();
+        if !matches!(&result, Ok(segment) if segment.marker != SOS && segment.marker != EOI) {
+            self.done = true;
+        }
+
+        Some(result.map_err(|_| offset))
+    }
+}
+
+impl<'a> Segments<'a> {
+    fn read_segment(&mut self) -> Result<Segment<'a>, ()> {
+        let data = self.data;
+
+        if data.get(self.pos) != Some(&0xFF) {
+            return Err(());
+        }
+
+        // 跳过填充用的0xFF
+        while data.get(self.pos) == Some(&0xFF) {
+            self.pos += 1;
+        }
+
+        let marker = *data.get(self.pos).ok_or(())?;
+        self.pos += 1;
+
+        // RSTn、TEM和EOI没有长度字段
+        if (0xD0..=0xD7).contains(&marker) || marker == 0x01 || marker == EOI {
+            return Ok(Segment { marker, payload: &[] });
+        }
+
+        let len_bytes = data.get(self.pos..self.pos + 2).ok_or(())?;
+        let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
+        if len < 2 {
+            return Err(());
+        }
+
+        let payload = data.get(self.pos + 2..self.pos + len).ok_or(())?;
+        self.pos += len;
+
+        Ok(Segment { marker, payload })
+    }
+}
+
+/// 是否为帧起始标记（SOF0~SOF15，排除DHT、JPG和DAC）
+pub(crate) fn is_sof(marker: u8) -> bool {
+    (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC)
+}
+
+/// 是否为渐进式帧起始标记
+pub(crate) fn is_progressive_sof(marker: u8) -> bool {
+    matches!(marker, SOF2 | 0xC6 | 0xCA | 0xCE)
+}
+
+/// JPEG文件头信息
+pub(crate) struct JpegInfo {
+    /// 采样精度（位）
+    pub(crate) precision: u8,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// 各分量的(水平, 垂直)采样系数
+    pub(crate) sampling: Vec<(u8, u8)>,
+    pub(crate) progressive: bool,
+    pub(crate) has_icc_profile: bool,
+}
+
+impl JpegInfo {
+    /// 按分量数推断的颜色类型：1为灰度，3通常为YCbCr，4通常为CMYK
+    pub(crate) fn color_type(&self) -> &'static str {
+        match self.sampling.len() {
+            1 => "grayscale",
+            3 => "ycbcr",
+            4 => "cmyk",
+            _ => "unknown",
+        }
+    }
+
+    /// 色度抽样方式，如"4:2:0"；单分量图片为"grayscale"，无法归类时为"unknown"
+    pub(crate) fn subsampling(&self) -> &'static str {
+        match self.sampling.as_slice() {
+            [_] => "grayscale",
+            [(luma_h, luma_v), (chroma_h, chroma_v), ..] if *chroma_h > 0 && *chroma_v > 0 => {
+                match (luma_h / chroma_h, luma_v / chroma_v) {
+                    (1, 1) => "4:4:4",
+                    (2, 1) => "4:2:2",
+                    (2, 2) => "4:2:0",
+                    (1, 2) => "4:4:0",
+                    (4, 1) => "4:1:1",
+                    (4, 2) => "4:1:0",
+                    _ => "unknown",
+                }
+            }
+            _ => "unknown",
+        }
+    }
+}
+
+/// 解析JPEG文件头，在SOS处停止
+/// 不是JPEG或在SOS之前没有找到SOF时返回None
+pub(crate) fn inspect(data: &[u8]) -> Option<JpegInfo> {
+    let mut frame: Option<JpegInfo> = None;
+    let mut has_icc_profile = false;
+
+    for segment in segments(data)? {
+        let Ok(segment) = segment else { break };
+
+        if segment.marker == APP2 && segment.payload.starts_with(ICC_PROFILE_ID) {
+            has_icc_profile = true;
+        }
+
+        if frame.is_none() && is_sof(segment.marker) {
+            frame = parse_sof(segment.marker, segment.payload);
+        }
+    }
+
+    frame.map(|info| JpegInfo { has_icc_profile, ..info })
+}
+
+/// 解析SOF段：精度(1) + 高(2) + 宽(2) + 分量数(1)，每个分量为ID(1) + 采样系数(1) + 量化表(1)
+fn parse_sof(marker: u8, payload: &[u8]) -> Option<JpegInfo> {
+    let header = payload.get(..6)?;
+    let precision = header[0];
+    let height = u32::from(u16::from_be_bytes([header[1], header[2]]));
+    let width = u32::from(u16::from_be_bytes([header[3], header[4]]));
+    let components = header[5] as usize;
+
+    let sampling = payload
+        .get(6..6 + components * 3)?
+        .chunks_exact(3)
+        .map(|component| (component[1] >> 4, component[1] & 0x0F))
+        .collect();
+
+    Some(JpegInfo {
+        precision,
+        width,
+        height,
+        sampling,
+        progressive: is_progressive_sof(marker),
+        has_icc_profile: false,
+    })
+}
+
+/// 提取并拼接ICC配置文件
+/// 较大的配置文件会拆分到多个APP2段，每段以标识、序号(1起)和总段数开头。
+/// 各段按序号重新排序后拼接；序号缺失、重复或总段数不一致时返回None
+pub(crate) fn icc_profile(data: &[u8]) -> Option<Vec<u8>> {
+    let mut chunks: Vec<(u8, &[u8])> = Vec::new();
+    let mut total: Option<u8> = None;
+
+    for segment in segments(data)? {
+        let Ok(segment) = segment else { break };
+        if segment.marker != APP2 {
+            continue;
+        }
+
+        let Some(header) = segment.payload.strip_prefix(ICC_PROFILE_ID) else { continue };
+        let [sequence, count, body @ ..] = header else { return None };
+
+        if *total.get_or_insert(*count) != *count {
+            return None;
+        }
+        chunks.push((*sequence, body));
+    }
+
+    let total = total?;
+    chunks.sort_by_key(|(sequence, _)| *sequence);
+    if chunks.len() != total as usize || chunks.iter().enumerate().any(|(i, (sequence, _))| *sequence as usize != i + 1) {
+        return None;
+    }
+
+    Some(chunks.into_iter().flat_map(|(_, body)| body.iter().copied()).collect())
+}
+
+/// SOS之前标记段结构的检查结果
+pub(crate) struct Structure {
+    /// 依次遇到的标记（不含0xFF前缀），以SOI（0xD8）开头
+    pub(crate) markers: Vec<u8>,
+    /// 标记段截断或格式错误的偏移
+    pub(crate) truncated_at: Option<usize>,
+    pub(crate) valid: bool,
+}
+
+/// 从SOI开始遍历标记段，确认SOF出现在SOS之前且DRI段长度正确
+/// 不是JPEG时返回None
+pub(crate) fn validate_structure(data: &[u8]) -> Option<Structure> {
+    let mut markers = vec![0xD8];
+    let mut truncated_at = None;
+    let mut seen_sof = false;
+    let mut valid = false;
+
+    for segment in segments(data)? {
+        let segment = match segment {
+            Ok(segment) => segment,
+            Err(offset) => {
+                truncated_at = Some(offset);
+                break;
+            }
+        };
+        markers.push(segment.marker);
+
+        if segment.marker == DRI && segment.payload.len() != 2 {
+            break;
+        }
+        if is_sof(segment.marker) {
+            seen_sof = true;
+        }
+        if segment.marker == SOS {
+            valid = seen_sof;
+        }
+    }
+
+    Some(Structure { markers, truncated_at, valid })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造带长度字段的标记段
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xFF, marker];
+        bytes.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// ICC配置文件的一个APP2段
+    fn icc_segment(sequence: u8, count: u8, body: &[u8]) -> Vec<u8> {
+        let mut payload = ICC_PROFILE_ID.to_vec();
+        payload.extend_from_slice(&[sequence, count]);
+        payload.extend_from_slice(body);
+        segment(APP2, &payload)
+    }
+
+    /// SOI + 给定标记段 + SOS + EOI
+    fn jpeg(segments: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        for bytes in segments {
+            data.extend_from_slice(bytes);
+        }
+        data.extend_from_slice(&segment(SOS, &[0; 10]));
+        data.extend_from_slice(&[0x00, 0xFF, EOI]);
+        data
+    }
+
+    #[test]
+    fn reassembles_multi_segment_profile_in_sequence_order() {
+        let data = jpeg(&[
+            segment(0xE0, b"JFIF\0\x01\x02\0\0\x01\0\x01\0\0"),
+            icc_segment(2, 3, b"second-"),
+            icc_segment(1, 3, b"first-"),
+            segment(0xDB, &[0; 65]),
+            icc_segment(3, 3, b"third"),
+        ]);
+
+        assert_eq!(icc_profile(&data).as_deref(), Some(&b"first-second-third"[..]));
+    }
+
+    #[test]
+    fn single_segment_profile() {
+        let data = jpeg(&[icc_segment(1, 1, b"profile")]);
+        assert_eq!(icc_profile(&data).as_deref(), Some(&b"profile"[..]));
+    }
+
+    #[test]
+    fn rejects_missing_segment() {
+        let data = jpeg(&[icc_segment(1, 3, b"first-"), icc_segment(3, 3, b"third")]);
+        assert_eq!(icc_profile(&data), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_segment() {
+        let data = jpeg(&[icc_segment(1, 2, b"first-"), icc_segment(1, 2, b"first-")]);
+        assert_eq!(icc_profile(&data), None);
+    }
+
+    #[test]
+    fn rejects_inconsistent_segment_count() {
+        let data = jpeg(&[icc_segment(1, 2, b"first-"), icc_segment(2, 3, b"second")]);
+        assert_eq!(icc_profile(&data), None);
+    }
+
+    #[test]
+    fn ignores_segments_after_start_of_scan() {
+        let mut data = jpeg(&[icc_segment(1, 1, b"profile")]);
+        data.extend_from_slice(&icc_segment(2, 1, b"ignored"));
+        assert_eq!(icc_profile(&data).as_deref(), Some(&b"profile"[..]));
+    }
+
+    #[test]
+    fn no_profile_without_app2() {
+        let data = jpeg(&[segment(0xE0, b"JFIF\0")]);
+        assert_eq!(icc_profile(&data), None);
+        assert_eq!(icc_profile(b"not a jpeg"), None);
+    }
+}
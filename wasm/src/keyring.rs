@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Locale};
+use crate::ImageDecryptor;
+
+/// 密文头部密钥ID的长度（字节）
+pub(crate) const KID_SIZE: usize = 4;
+
+/// 密钥环
+/// 按密钥ID保存多组密钥和IV，配合`ImageDecryptor::decrypt_with_keyring`实现密钥轮换
+#[wasm_bindgen]
+pub struct Keyring {
+    // 经过校验的Base64密钥和IV，按4字节密钥ID索引
+    keys: HashMap<[u8; KID_SIZE], (String, String)>,
+}
+
+#[wasm_bindgen]
+impl Keyring {
+    /// 创建空的密钥环
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Keyring {
+        Keyring { keys: HashMap::new() }
+    }
+
+    /// 添加或替换一组密钥
+    /// 
+    /// # 参数
+    /// - `kid`: 密钥ID，必须恰好为4字节（如"k001"），与密文头部的前4字节对应
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    #[wasm_bindgen]
+    pub fn add_key(&mut self, kid: &str, key_base64: &str, iv_base64: &str) -> Result<(), JsValue> {
+        self.insert(kid, key_base64, iv_base64).map_err(|e| e.to_js(Locale::default()))
+    }
+
+    /// 已注册的密钥数量
+    #[wasm_bindgen]
+    pub fn size(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl Default for Keyring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keyring {
+    fn insert(&mut self, kid: &str, key_base64: &str, iv_base64: &str) -> Result<(), Error> {
+        let kid: [u8; KID_SIZE] = kid
+            .as_bytes()
+            .try_into()
+            .map_err(|_| Error::KeyIdLength(kid.len()))?;
+
+        // 添加时即校验，避免解密时才发现密钥无效
+        ImageDecryptor::decode_key(key_base64)?;
+        ImageDecryptor::decode_iv(iv_base64)?;
+
+        self.keys.insert(kid, (key_base64.to_string(), iv_base64.to_string()));
+        Ok(())
+    }
+
+    /// 按密钥ID查找Base64密钥和IV
+    pub(crate) fn lookup(&self, kid: &[u8]) -> Result<(&str, &str), Error> {
+        kid.try_into()
+            .ok()
+            .and_then(|kid: [u8; KID_SIZE]| self.keys.get(&kid))
+            .map(|(key, iv)| (key.as_str(), iv.as_str()))
+            .ok_or_else(|| Error::UnknownKeyId(String::from_utf8_lossy(kid).into_owned()))
+    }
+}
@@ -2,21 +2,223 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use js_sys::Uint8Array;
 use aes::Aes256;
-use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use cbc::cipher::{BlockDecryptMut, InnerIvInit, KeyIvInit};
 use base64::{Engine as _, engine::general_purpose};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+mod cbc_hmac;
+mod config;
+mod ctr_mode;
+mod error;
+mod format;
+mod gcm_mode;
+mod gif;
+mod hash;
+mod icc;
+mod incremental;
+mod jpeg;
+mod keyring;
+mod padding;
+mod png;
+mod prepared;
+mod selftest;
+mod raster;
+mod tiff;
+
+use error::{Error, Locale};
+
+pub use config::{default_config, DecryptorConfig};
+pub use ctr_mode::CtrCounter;
+pub use hash::IncrementalHasher;
+pub use incremental::IncrementalDecryptor;
+pub use keyring::Keyring;
+pub use prepared::PreparedKey;
 
 // 当panic发生时，提供更好的错误信息
 #[cfg(feature = "console_error_panic_hook")]
 pub use console_error_panic_hook::set_once as set_panic_hook;
 
+// 体积优化构建使用wee_alloc作为全局分配器
+#[cfg(feature = "small")]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+
+// 当前生效的全局分配器名称
+#[cfg(feature = "small")]
+const ALLOCATOR: &str = "wee_alloc";
+#[cfg(not(feature = "small"))]
+const ALLOCATOR: &str = "default";
+
 // AES-256-CBC解密器类型别名
 type Aes256CbcDec = cbc::Decryptor<Aes256>;
 
+/// 日志输出到浏览器控制台
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn log(s: &str);
+}
+
+/// 运行时日志开关，由set_logging_enabled设置
+static LOGGING_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 宏：简化控制台日志输出
+/// 未启用`logging`特性或运行时关闭日志时不输出
+macro_rules! console_log {
+    ($($t:tt)*) => (
+        if cfg!(feature = "logging") && LOGGING_ENABLED.load(Ordering::Relaxed) {
+            log(&format_args!($($t)*).to_string())
+        }
+    )
+}
+
 /// WebAssembly图片解密模块
 /// 提供高性能的AES-256-CBC解密功能
 #[wasm_bindgen]
 pub struct ImageDecryptor {
     // 移除chunk_size字段，因为CBC模式不支持真正的分块解密
+
+    // 等待copy_result取回的解密结果，按令牌索引
+    pending_results: HashMap<u32, Vec<u8>>,
+    next_token: u32,
+
+    // decrypt_image_view返回的视图所指向的解密结果，下次调用时被替换
+    view_buffer: Vec<u8>,
+
+    // 允许解密的最大输入字节数，None表示不限制
+    max_input_size: Option<usize>,
+
+    // 错误信息语言
+    locale: Locale,
+
+    // IV来源
+    iv_mode: IvMode,
+
+    // 是否严格校验IV长度，关闭后缺少前导零的IV会在左侧补零
+    strict_iv: bool,
+
+    // 是否在移除填充后再去掉明文末尾的一个换行符
+    trim_trailing_newline: bool,
+
+    // PKCS7填充的分组长度，与AES分组长度无关
+    pad_block_size: usize,
+
+    // 是否已输出过全零IV警告，每个实例只提示一次
+    zero_iv_warned: Cell<bool>,
+
+    // 解密统计，与异步任务使用的配置副本共享
+    stats: Rc<DecryptStats>,
+
+    // 调用方注册的自定义文件签名及其MIME类型，按注册顺序保存
+    custom_magic: Vec<(Vec<u8>, String)>,
+}
+
+/// 实例生命周期内的解密统计
+#[derive(Default)]
+struct DecryptStats {
+    decrypt_count: Cell<u32>,
+    failure_count: Cell<u32>,
+    bytes_processed: Cell<u64>,
+    // 上一次解密处理的16字节块数，失败时为0
+    last_blocks: Cell<u32>,
+}
+
+/// decrypt_to_stream每次读取、decrypt_chunked等分段解密时每段的密文字节数
+const STREAM_CHUNK_SIZE: u32 = 64 * 1024;
+
+/// decrypt_file_handle对超过该大小的文件分段读取和解密，不一次性读入全部密文
+const FILE_STREAM_THRESHOLD: usize = 8 * 1024 * 1024;
+
+/// decrypt_gzipped在未设置最大输入大小时，解压结果的默认上限
+const GUNZIP_DEFAULT_LIMIT: usize = 256 * 1024 * 1024;
+
+/// 试运行时解密的开头块数，足以覆盖常见图片格式的文件头
+const DRY_RUN_PREFIX_BLOCKS: usize = 4;
+
+/// IV来源模式
+/// 
+/// - `Explicit`: 使用调用方传入的`iv_base64`（默认）
+/// - `Zero`: 使用16字节全零IV，忽略`iv_base64`参数
+/// - `FirstBlock`: 将密文的前16字节作为IV，只解密其后的数据，忽略`iv_base64`参数。
+///   这些字节本身就是CBC链中的第一个密文块，不会出现在解密结果中；
+///   与另行拼接在密文前、需要调用方自行拆分后通过`iv_base64`传入的IV不同，
+///   调用方无需做任何拆分
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IvMode {
+    Explicit,
+    Zero,
+    FirstBlock,
+}
+
+/// 分组密码工作模式
+/// 
+/// - `Cbc`: AES-CBC + PKCS7填充，密文比明文多1~16字节
+/// - `Ctr`: AES-CTR等流式模式，密文与明文等长
+/// - `Gcm`: AES-GCM，密文比明文多16字节认证标签（不含nonce）
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CipherMode {
+    Cbc,
+    Ctr,
+    Gcm,
+}
+
+/// gzip压缩与加密的先后顺序
+/// 
+/// - `CompressThenEncrypt`: 先压缩后加密，解密时先解密再解压
+/// - `EncryptThenCompress`: 先加密后压缩，解密时先解压再解密
+/// 
+/// 两种顺序不能互换：顺序与数据不符时会得到无效数据，或在解压、移除填充时报错
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerOrder {
+    CompressThenEncrypt,
+    EncryptThenCompress,
+}
+
+/// 检测到的图片格式
+/// 与`detect_image_type`返回的MIME类型一一对应，无法识别或为自定义签名的其他类型时为`Unknown`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Unknown,
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Qoi,
+    Bmp,
+    Tiff,
+    Dng,
+    Cr2,
+    Nef,
+    Arw,
+}
+
+impl ImageFormat {
+    /// 由MIME类型得到对应的格式
+    fn from_mime(mime: &str) -> ImageFormat {
+        match mime {
+            "image/jpeg" => ImageFormat::Jpeg,
+            "image/png" => ImageFormat::Png,
+            "image/gif" => ImageFormat::Gif,
+            "image/webp" => ImageFormat::WebP,
+            "image/qoi" => ImageFormat::Qoi,
+            "image/bmp" => ImageFormat::Bmp,
+            "image/tiff" => ImageFormat::Tiff,
+            "image/x-adobe-dng" => ImageFormat::Dng,
+            "image/x-canon-cr2" => ImageFormat::Cr2,
+            "image/x-nikon-nef" => ImageFormat::Nef,
+            "image/x-sony-arw" => ImageFormat::Arw,
+            _ => ImageFormat::Unknown,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -24,10 +226,131 @@ impl ImageDecryptor {
     /// 创建新的解密器实例
     #[wasm_bindgen(constructor)]
     pub fn new() -> ImageDecryptor {
-        #[cfg(feature = "console_error_panic_hook")]
-        set_panic_hook();
-        
-        ImageDecryptor {}
+        Self::from_config(&DecryptorConfig::default())
+    }
+
+    /// 按给定配置创建解密器实例
+    /// 
+    /// # 参数
+    /// - `config`: 解密器配置，创建后对配置的修改不影响该实例
+    #[wasm_bindgen]
+    pub fn with_config(config: &DecryptorConfig) -> ImageDecryptor {
+        if !config.strict_iv {
+            console_log!("⚠️ 已关闭IV严格校验：缺少前导零的IV将在左侧补零，仅应用于读取已知有缺陷的历史数据");
+        }
+
+        Self::from_config(config)
+    }
+
+    /// 设置IV来源模式
+    /// Zero和FirstBlock模式下解密方法会忽略`iv_base64`参数，可传入空字符串
+    /// 
+    /// # 参数
+    /// - `mode`: IV来源模式，默认为Explicit
+    #[wasm_bindgen]
+    pub fn set_iv_mode(&mut self, mode: IvMode) {
+        self.iv_mode = mode;
+    }
+
+    /// 设置是否严格校验IV长度
+    /// 宽松模式只用于读取IV缺少前导零的历史数据：Base64解码为15字节、
+    /// 或为30~31位十六进制字符串的IV会在左侧补零到16字节，并在控制台输出警告。
+    /// 只修复丢失的前导零，不接受其他编码或长度。
+    /// 注意补零得到的是另一个IV，只有原始IV确实以零开头时才能正确解密；
+    /// 不要对新数据关闭严格模式
+    /// 
+    /// # 参数
+    /// - `strict`: 是否严格校验，默认为true
+    #[wasm_bindgen]
+    pub fn set_strict_iv(&mut self, strict: bool) {
+        if !strict {
+            console_log!("⚠️ 已关闭IV严格校验：缺少前导零的IV将在左侧补零，仅应用于读取已知有缺陷的历史数据");
+        }
+        self.strict_iv = strict;
+    }
+
+    /// 设置是否去掉明文末尾的一个换行符
+    /// 用于兼容在填充前追加了换行符的加密脚本：移除PKCS7填充后，
+    /// 若明文以`\n`或`\r\n`结尾则去掉这一个换行符。对所有CBC方法生效，包括流式、分段和增量解密
+    /// 
+    /// # 参数
+    /// - `trim`: 是否去掉末尾换行符，默认为false
+    #[wasm_bindgen]
+    pub fn set_trim_trailing_newline(&mut self, trim: bool) {
+        self.trim_trailing_newline = trim;
+    }
+
+    /// 设置PKCS7填充的分组长度
+    /// AES分组固定为16字节，但部分旧工具先按8字节分组填充再交给AES加密，
+    /// 此时需设为8才能正确移除填充。对所有CBC方法生效，包括流式、分段和增量解密
+    /// 
+    /// # 参数
+    /// - `size`: 填充分组长度，只能为8或16，默认为16
+    #[wasm_bindgen]
+    pub fn set_padding_block_size(&mut self, size: usize) -> Result<(), JsValue> {
+        self.pad_block_size = padding::check_block_size(size).map_err(|e| e.to_js(self.locale))?;
+        Ok(())
+    }
+
+    /// 注册自定义文件签名
+    /// peek_type、verify_decrypted_image等方法的类型检测会先匹配自定义签名，再使用内置规则；
+    /// 多个签名都匹配时以最后注册的为准
+    /// 
+    /// # 参数
+    /// - `signature`: 文件开头的签名字节，不能为空
+    /// - `mime`: 匹配时返回的MIME类型
+    #[wasm_bindgen]
+    pub fn register_magic(&mut self, signature: &Uint8Array, mime: &str) -> Result<(), JsValue> {
+        if signature.length() == 0 {
+            return Err(Error::EmptySignature.to_js(self.locale));
+        }
+
+        self.custom_magic.push((signature.to_vec(), mime.to_string()));
+        Ok(())
+    }
+
+    /// 列出已注册的自定义文件签名
+    /// 
+    /// # 返回
+    /// 按注册顺序排列的对象数组，每项包含signatureHex（小写十六进制签名）和mime字段
+    #[wasm_bindgen]
+    pub fn list_magic(&self) -> JsValue {
+        let list = js_sys::Array::new();
+        for (signature, mime) in &self.custom_magic {
+            let entry = js_sys::Object::new();
+            js_sys::Reflect::set(&entry, &"signatureHex".into(), &hash::to_hex(signature).into()).unwrap();
+            js_sys::Reflect::set(&entry, &"mime".into(), &mime.into()).unwrap();
+            list.push(&entry);
+        }
+
+        list.into()
+    }
+
+    /// 清除所有自定义文件签名，类型检测恢复为只使用内置规则
+    #[wasm_bindgen]
+    pub fn clear_magic(&mut self) {
+        self.custom_magic.clear();
+    }
+
+    /// 设置错误信息语言
+    /// 
+    /// # 参数
+    /// - `lang`: 语言代码，支持"zh"（默认）和"en"
+    #[wasm_bindgen]
+    pub fn set_locale(&mut self, lang: &str) -> Result<(), JsValue> {
+        self.locale = Locale::parse(lang)
+            .ok_or_else(|| Error::UnsupportedLocale(lang.to_string()).to_js(self.locale))?;
+        Ok(())
+    }
+
+    /// 设置允许解密的最大输入字节数
+    /// 超过限制的输入会在分配工作缓冲区之前被拒绝，用于防范来自不可信来源的超大数据
+    /// 
+    /// # 参数
+    /// - `bytes`: 最大输入字节数，默认不限制
+    #[wasm_bindgen]
+    pub fn set_max_input_size(&mut self, bytes: usize) {
+        self.max_input_size = Some(bytes);
     }
 
     /// 解密图片数据
@@ -49,163 +372,2782 @@ impl ImageDecryptor {
     ) -> Result<Uint8Array, JsValue> {
         match self.decrypt_bytes_internal(encrypted_data, key_base64, iv_base64) {
             Ok(decrypted) => Ok(decrypted),
-            Err(e) => Err(JsValue::from_str(&e))
+            Err(e) => Err(e.to_js(self.locale))
         }
     }
 
-    /// 优化的解密图片数据方法
-    /// 提供与decrypt_image相同的功能，但针对渐进式解密进行了优化
+    /// 解密JSON信封中的数据
+    /// 信封格式为`{ "iv": "...", "data": "...", "alg": "AES-256-CBC" }`，iv和data均为Base64。
+    /// alg支持AES-256-CBC（iv为16字节）、AES-256-GCM（iv为12字节nonce，data末尾带16字节tag）
+    /// 和AES-256-CTR（iv为16字节，128位大端计数器），不区分大小写。CBC总是使用信封中的iv，不受IV模式设置影响
+    /// 
+    /// # 参数
+    /// - `envelope_json`: JSON信封字符串
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；JSON无效、缺少字段或算法不支持时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_envelope(&self, envelope_json: &str, key_base64: &str) -> Result<Uint8Array, JsValue> {
+        let decrypted = self
+            .open_envelope(envelope_json, key_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 使用原始字节形式的密钥和IV解密图片数据
+    /// 密钥可直接使用`crypto.subtle.exportKey("raw", key)`导出的ArrayBuffer，无需先转成Base64。
+    /// 总是使用传入的IV，不受IV模式设置影响
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_buffer`: 32字节原始密钥
+    /// - `iv_buffer`: 16字节原始初始化向量
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_image_rawkey(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_buffer: &js_sys::ArrayBuffer,
+        iv_buffer: &js_sys::ArrayBuffer,
+    ) -> Result<Uint8Array, JsValue> {
+        let key = Uint8Array::new(key_buffer).to_vec();
+        let iv = Uint8Array::new(iv_buffer).to_vec();
+
+        let result = self.decrypt_cbc_raw(encrypted_data, &key, &iv);
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 解密图片数据，并由调用方指明密文是否带有PKCS7填充
+    /// CBC密文总是16字节对齐，无法从长度判断明文末尾是否有填充块。
+    /// 用于读取未经填充、明文本身恰好按16字节对齐的历史数据，此时不移除任何字节
     /// 
     /// # 参数
     /// - `encrypted_data`: 加密的图片数据
     /// - `key_base64`: Base64编码的32字节密钥
     /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `pad_present`: 是否带有PKCS7填充，不传时为true（与decrypt_image相同）
     /// 
     /// # 返回
     /// 解密后的图片数据，如果失败则返回错误
     #[wasm_bindgen]
-    pub fn decrypt_image_optimized(
+    pub fn decrypt_image_with_pad_hint(
         &self,
         encrypted_data: &Uint8Array,
         key_base64: &str,
         iv_base64: &str,
+        pad_present: Option<bool>,
     ) -> Result<Uint8Array, JsValue> {
-        // 使用相同的内部实现，但可以在未来添加优化
-        match self.decrypt_bytes_internal(encrypted_data, key_base64, iv_base64) {
-            Ok(decrypted) => Ok(decrypted),
-            Err(e) => Err(JsValue::from_str(&e))
-        }
+        let result = if pad_present.unwrap_or(true) {
+            self.decrypt_cbc(encrypted_data, key_base64, iv_base64, self.iv_mode)
+        } else {
+            self.decrypt_cbc_blocks(encrypted_data, key_base64, iv_base64, self.iv_mode)
+        };
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
     }
 
-    /// 验证解密结果
+    /// 使用16字节全零IV解密图片数据
+    /// 用于读取以全零IV加密的历史数据，免去调用方手动构造Base64编码的全零IV。
+    /// 固定IV会削弱CBC的安全性，首次调用时会在控制台输出一次警告
     /// 
     /// # 参数
-    /// - `decrypted_data`: 解密后的数据
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
     /// 
     /// # 返回
-    /// 验证结果和文件类型信息
+    /// 解密后的图片数据，如果失败则返回错误
     #[wasm_bindgen]
-    pub fn verify_decrypted_image(&self, decrypted_data: &Uint8Array) -> JsValue {
-        let data = decrypted_data.to_vec();
-        
-        if data.len() < 8 {
-            return js_sys::JSON::stringify(&js_sys::Object::new()).unwrap().into();
+    pub fn decrypt_image_zero_iv(&self, encrypted_data: &Uint8Array, key_base64: &str) -> Result<Uint8Array, JsValue> {
+        if !self.zero_iv_warned.replace(true) {
+            console_log!("⚠️ 正在使用全零IV解密：重复使用固定IV会削弱AES-CBC的安全性，仅应用于读取历史数据");
         }
 
-        let result = js_sys::Object::new();
-        
-        // 检测文件类型
-        let file_type = self.detect_image_type(&data);
-        js_sys::Reflect::set(&result, &"fileType".into(), &JsValue::from_str(&file_type)).unwrap();
-        
-        // 验证文件头
-        let is_valid = !file_type.is_empty();
-        js_sys::Reflect::set(&result, &"isValid".into(), &is_valid.into()).unwrap();
-        
-        // 文件大小
-        js_sys::Reflect::set(&result, &"fileSize".into(), &(data.len() as u32).into()).unwrap();
-        
-        result.into()
+        let decrypted = self
+            .decrypt_with_iv_mode(encrypted_data, key_base64, "", IvMode::Zero)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
     }
 
-    /// 获取性能统计信息
+    /// 解密以Base64 data URL形式保存的密文
+    /// 如`data:application/octet-stream;base64,...`，MIME类型可省略
+    /// 
+    /// # 参数
+    /// - `encrypted_data_url`: 包含密文的data URL
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；不是data URL、缺少base64标记或按Base64长度估算超过最大输入限制时返回错误
     #[wasm_bindgen]
-    pub fn get_performance_info(&self) -> JsValue {
-        let info = js_sys::Object::new();
-        
-        // WASM特性检测
-        js_sys::Reflect::set(&info, &"wasmSupported".into(), &true.into()).unwrap();
-        
-        // 内存使用情况（简化实现）
-        let memory_pages = wasm_bindgen::memory()
-            .dyn_into::<js_sys::WebAssembly::Memory>()
-            .ok()
-            .and_then(|mem| mem.buffer().dyn_into::<js_sys::ArrayBuffer>().ok())
-            .map(|buf| buf.byte_length() / (64 * 1024))
-            .unwrap_or(0);
+    pub fn decrypt_data_url(
+        &self,
+        encrypted_data_url: &str,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let payload = format::data_url_payload(encrypted_data_url).map_err(|e| e.to_js(self.locale))?;
+        self.check_base64_len(payload.len()).map_err(|e| e.to_js(self.locale))?;
+        let ciphertext = general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|e| Error::DataUrlBase64(e.to_string()).to_js(self.locale))?;
 
-        js_sys::Reflect::set(&info, &"memoryPages".into(), &memory_pages.into()).unwrap();
-        
-        info.into()
+        self.decrypt_bytes_internal(&Uint8Array::from(&ciphertext[..]), key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))
     }
-}
 
-impl ImageDecryptor {
-    /// 内部解密方法 - 统一的高性能解密实现
-    /// 减少数据复制，提高性能，统一错误处理
-    fn decrypt_bytes_internal(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> Result<Uint8Array, String> {
-        // 统一的输入验证
-        if key_base64.is_empty() {
-            return Err("密钥不能为空".to_string());
-        }
-        
-        if iv_base64.is_empty() {
-            return Err("IV不能为空".to_string());
-        }
-        
-        if encrypted_data.length() == 0 {
-            return Err("加密数据不能为空".to_string());
-        }
-        
-        // 验证加密数据长度（必须是16字节的倍数）
-        if encrypted_data.length() % 16 != 0 {
-            return Err("加密数据长度必须是16字节的倍数".to_string());
-        }
+    /// 解密并校验、移除明文末尾的CRC32
+    /// 明文格式为`图片数据 || CRC32(4字节，大端)`，CRC32按图片数据计算
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 去掉校验值后的图片数据；明文不足4字节或校验值不匹配时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_and_strip_crc(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
 
-        // 解码密钥和IV
-        let key = general_purpose::STANDARD
-            .decode(key_base64)
-            .map_err(|e| format!("密钥Base64解码失败: {}", e))?;
-        
-        let iv = general_purpose::STANDARD
-            .decode(iv_base64)
-            .map_err(|e| format!("IV Base64解码失败: {}", e))?;
+        let body = format::strip_crc32(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        Ok(Uint8Array::from(body))
+    }
 
-        // 验证长度
-        if key.len() != 32 {
-            return Err(format!("密钥长度必须为32字节，当前为{}字节", key.len()));
-        }
-        
-        if iv.len() != 16 {
-            return Err(format!("IV长度必须为16字节，当前为{}字节", iv.len()));
-        }
+    /// 解密并同时计算明文的缓存键
+    /// 按64KB分段解密，每段明文产生后立即计入SHA-256，无需在解密后再扫描一遍明文
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含data（解密后的图片数据）和cacheKey（明文SHA-256前8字节的十六进制）字段的对象
+    #[wasm_bindgen]
+    pub fn decrypt_with_cache_key(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<JsValue, JsValue> {
+        let mut hasher = Sha256::new();
+        let result = self.decrypt_in_chunks(encrypted_data, key_base64, iv_base64, |part| {
+            hasher.update(part);
+            Ok(())
+        });
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
 
-        // 创建解密器
-        let cipher = Aes256CbcDec::new_from_slices(&key, &iv)
-            .map_err(|e| format!("AES解密器初始化失败: {}", e))?;
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"data".into(), &Uint8Array::from(&decrypted[..])).unwrap();
+        js_sys::Reflect::set(&result, &"cacheKey".into(), &hash::cache_key(hasher).into()).unwrap();
 
-        // 直接从Uint8Array创建buffer，避免额外复制
-        let mut buffer = encrypted_data.to_vec();
-        
-        // 验证buffer不为空
-        if buffer.is_empty() {
-            return Err("解密缓冲区为空".to_string());
-        }
-        
-        // 执行解密并移除填充
-        // 使用更详细的错误处理来诊断Unpad Error
-        let buffer_len = buffer.len();
-        let last_16_bytes = buffer[buffer_len.saturating_sub(16)..].to_vec();
-        
-        let decrypted = cipher.decrypt_padded_mut::<cbc::cipher::block_padding::Pkcs7>(&mut buffer)
-            .map_err(|_e| {
-                // UnpadError是单元结构体，只有一种错误情况
-                format!("PKCS7填充验证失败: 数据长度={}, 最后16字节={:?}", 
-                       buffer_len, 
-                       last_16_bytes)
-            })?;
+        Ok(result.into())
+    }
 
-        // 验证解密结果
-        if decrypted.is_empty() {
-            return Err("解密结果为空".to_string());
+    /// 解密并识别图片类型，同时完整解码以确认数据没有被截断
+    /// 严格模式（默认）下文件头无法识别或解码失败都返回错误；宽松模式下文件头有效但解码失败时
+    /// （如网络丢失了末尾数据）仍返回解密结果，并将partial置为true、在warning中给出原因，
+    /// 便于像浏览器一样显示部分加载的图片。无法由本模块解码的格式（如TIFF）只检查文件头。
+    /// 传入声明的MIME类型（如CDN返回的Content-Type）时，额外报告它与实际类型是否一致，
+    /// 比较时忽略大小写和参数，`image/jpg`视为`image/jpeg`；不一致不会导致返回错误
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `lenient`: 是否启用宽松模式，不传时为false
+    /// - `declared_mime`: 可选的声明MIME类型
+    /// 
+    /// # 返回
+    /// 包含data、fileType、partial和warning（没有警告时为null）字段的对象；
+    /// 传入declared_mime时还包含declaredMime、actualMime和mimeMatches字段
+    #[wasm_bindgen]
+    pub fn decrypt_and_identify(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        lenient: Option<bool>,
+        declared_mime: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let file_type = self.detect_image_type(&decrypted);
+        if file_type.is_empty() {
+            return Err(Error::UnknownImageType.to_js(self.locale));
+        }
+
+        let mut warning = JsValue::NULL;
+        if raster::can_decode(&decrypted) {
+            if let Err(e) = raster::decode(&decrypted) {
+                if !lenient.unwrap_or(false) {
+                    return Err(e.to_js(self.locale));
+                }
+                warning = e.message(self.locale).into();
+            }
+        }
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"data".into(), &Uint8Array::from(&decrypted[..])).unwrap();
+        js_sys::Reflect::set(&result, &"fileType".into(), &JsValue::from_str(&file_type)).unwrap();
+        js_sys::Reflect::set(&result, &"partial".into(), &(!warning.is_null()).into()).unwrap();
+        js_sys::Reflect::set(&result, &"warning".into(), &warning).unwrap();
+
+        if let Some(declared_mime) = declared_mime {
+            let mime_matches = format::normalize_mime(&declared_mime) == file_type;
+            js_sys::Reflect::set(&result, &"declaredMime".into(), &declared_mime.into()).unwrap();
+            js_sys::Reflect::set(&result, &"actualMime".into(), &file_type.into()).unwrap();
+            js_sys::Reflect::set(&result, &"mimeMatches".into(), &mime_matches.into()).unwrap();
+        }
+
+        Ok(result.into())
+    }
+
+    /// 解密经过gzip压缩的数据
+    /// 按`order`指定的顺序执行解压和解密，数据必须与所选顺序一致。
+    /// 输入先按最大输入大小检查；解压结果同样不能超过最大输入大小（未设置时为256MB）
+    /// 
+    /// # 参数
+    /// - `data`: 加密并压缩的数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `order`: 压缩与加密的先后顺序
+    /// 
+    /// # 返回
+    /// 解压并解密后的图片数据，解压失败或解密失败时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_gzipped(
+        &self,
+        data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        order: LayerOrder,
+    ) -> Result<Uint8Array, JsValue> {
+        self.check_input_size(data).map_err(|e| e.to_js(self.locale))?;
+        let limit = self.max_input_size.unwrap_or(GUNZIP_DEFAULT_LIMIT);
+
+        let decrypted = match order {
+            LayerOrder::CompressThenEncrypt => {
+                let decrypted = self
+                    .decrypt_to_vec(data, key_base64, iv_base64)
+                    .map_err(|e| e.to_js(self.locale))?;
+                format::gunzip(&decrypted, limit).map_err(|e| e.to_js(self.locale))?
+            }
+            LayerOrder::EncryptThenCompress => {
+                let ciphertext = format::gunzip(&data.to_vec(), limit).map_err(|e| e.to_js(self.locale))?;
+                self.decrypt_to_vec(&Uint8Array::from(&ciphertext[..]), key_base64, iv_base64)
+                    .map_err(|e| e.to_js(self.locale))?
+            }
+        };
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 解密并在第一个分隔符处拆分明文
+    /// 用于头部元数据（如JSON）与图片数据拼接后整体加密的容器格式
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `delimiter`: 分隔头部和正文的字节序列（如换行符）
+    /// 
+    /// # 返回
+    /// 包含header和body字段（均为Uint8Array，不含分隔符）的对象；
+    /// 找不到分隔符时header为空，body为完整明文
+    #[wasm_bindgen]
+    pub fn decrypt_split(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        delimiter: &Uint8Array,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let (header, body) = format::split_at_delimiter(&decrypted, &delimiter.to_vec());
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"header".into(), &Uint8Array::from(header)).unwrap();
+        js_sys::Reflect::set(&result, &"body".into(), &Uint8Array::from(body)).unwrap();
+
+        Ok(result.into())
+    }
+
+    /// 解密以明文长度前缀确定数据长度的格式
+    /// 明文格式为`长度(4字节，大端) || 数据 || 填充`，不依赖PKCS7填充确定长度，因此不校验填充；
+    /// 长度超过可用字节数，或之后剩余超过一个分组时视为不一致
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 长度前缀所声明的数据，长度不一致时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_with_length_prefix(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_cbc_blocks(encrypted_data, key_base64, iv_base64, self.iv_mode);
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
+
+        let body = format::length_prefixed(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        Ok(Uint8Array::from(body))
+    }
+
+    /// 解密并校验图片尺寸
+    /// 错误的密钥偶尔也能得到看似有效的文件头，与清单中记录的尺寸比对可进一步确认解密正确。
+    /// 尺寸从文件头解析，支持JPEG、PNG、GIF、WebP和QOI
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `expected_w`: 期望的宽度（像素）
+    /// - `expected_h`: 期望的高度（像素）
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；无法解析尺寸或尺寸不符时返回包含期望值和实际值的错误
+    #[wasm_bindgen]
+    pub fn decrypt_and_verify_dimensions(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        expected_w: u32,
+        expected_h: u32,
+    ) -> Result<Uint8Array, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let actual = header_dimensions(&decrypted).ok_or_else(|| Error::UnknownDimensions.to_js(self.locale))?;
+        let expected = (expected_w, expected_h);
+        if actual != expected {
+            return Err(Error::DimensionMismatch { expected, actual }.to_js(self.locale));
+        }
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 使用AES-256-CTR解密图片数据
+    /// CTR为流式模式，密文无需按16字节对齐，也没有填充
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始计数器块
+    /// - `counter`: 计数器格式，不传时默认为128位大端（`CtrCounter.Be128`）
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_image_ctr(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        counter: Option<CtrCounter>,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_ctr(encrypted_data, key_base64, iv_base64, counter.unwrap_or(CtrCounter::Be128));
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 解密`nonce(12) || 密文 || tag(16)`单一缓冲区布局的AES-256-GCM数据
+    /// 
+    /// # 参数
+    /// - `data`: nonce、密文和认证标签拼接后的数据，至少28字节
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；长度不足或认证标签校验失败时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_gcm_combined(&self, data: &Uint8Array, key_base64: &str) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_gcm(data, key_base64);
+        let decrypted = self.track(result, data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 使用单独传入的nonce解密AES-256-GCM数据
+    /// GCM的nonce为12字节，与CBC/CTR的16字节IV不同；传入其他长度时返回明确的nonce长度错误
+    /// 
+    /// # 参数
+    /// - `data`: 密文和认证标签拼接后的数据，至少16字节
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `nonce_base64`: Base64编码的12字节nonce
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；nonce长度错误或认证标签校验失败时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_gcm_with_nonce(
+        &self,
+        data: &Uint8Array,
+        key_base64: &str,
+        nonce_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_gcm_detached(data, key_base64, nonce_base64);
+        let decrypted = self.track(result, data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 解密AES_256_CBC_HMAC_SHA256记录（先加密后MAC）
+    /// 记录布局为`IV(16) || CBC密文 || HMAC-SHA256(32)`，认证标签为
+    /// `HMAC-SHA256(mac_key, aad || IV || 密文 || AL)`，AL为aad的比特长度（64位大端）。
+    /// 先以常量时间校验认证标签，校验通过后才解密，因此不会泄露填充信息
+    /// 
+    /// # 参数
+    /// - `record`: 完整的记录
+    /// - `enc_key_base64`: Base64编码的32字节加密密钥
+    /// - `mac_key_base64`: Base64编码的32字节MAC密钥
+    /// - `aad`: 参与认证但不加密的附加数据，可为空
+    /// 
+    /// # 返回
+    /// 解密后的数据；认证标签不匹配时返回错误且不解密
+    #[wasm_bindgen]
+    pub fn decrypt_cbc_hmac(
+        &self,
+        record: &Uint8Array,
+        enc_key_base64: &str,
+        mac_key_base64: &str,
+        aad: &Uint8Array,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.open_cbc_hmac(record, enc_key_base64, mac_key_base64, aad);
+        let decrypted = self.track(result, record).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 使用密钥环解密带密钥ID头部的数据
+    /// 数据布局为`密钥ID(4) || 密文`，按密钥ID在密钥环中查找对应的密钥和IV
+    /// 
+    /// # 参数
+    /// - `data`: 带密钥ID头部的加密数据
+    /// - `keyring`: 已注册密钥的密钥环
+    /// 
+    /// # 返回
+    /// 解密后的图片数据；密钥ID未注册时返回"未知密钥ID"错误
+    #[wasm_bindgen]
+    pub fn decrypt_with_keyring(&self, data: &Uint8Array, keyring: &Keyring) -> Result<Uint8Array, JsValue> {
+        let len = data.length() as usize;
+        if len <= keyring::KID_SIZE {
+            return Err(Error::MissingKeyId(len).to_js(self.locale));
+        }
+
+        let kid = data.subarray(0, keyring::KID_SIZE as u32).to_vec();
+        let (key_base64, iv_base64) = keyring.lookup(&kid).map_err(|e| e.to_js(self.locale))?;
+
+        let ciphertext = data.subarray(keyring::KID_SIZE as u32, data.length());
+        self.decrypt_bytes_internal(&ciphertext, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))
+    }
+
+    /// 使用预先完成密钥扩展的密钥解密
+    /// 适合批量解密同一密钥、不同IV的数据（如同一批缩略图），省去每次的密钥扩展；
+    /// IV来源仍遵循当前的IV模式
+    /// 
+    /// # 参数
+    /// - `prepared`: 由Base64密钥创建的PreparedKey
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `encrypted_data`: 加密的图片数据
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_with_prepared(
+        &self,
+        prepared: &PreparedKey,
+        iv_base64: &str,
+        encrypted_data: &Uint8Array,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_cbc_prepared(prepared, iv_base64, encrypted_data, self.iv_mode);
+        let decrypted = self.track(result, encrypted_data).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 使用同一密钥批量解密，每项可以有各自的IV
+    /// 每项可以是`{ data, iv_base64 }`对象，也可以是前16字节为IV的Uint8Array（此时忽略当前IV模式）。
+    /// 密钥只解码和扩展一次；单项失败不影响其他项
+    /// 
+    /// # 参数
+    /// - `items`: 待解密的项
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `on_error`: 可选的失败回调，每项失败时立即以(序号, 错误对象)调用，便于实时上报；
+    ///   回调的返回值被忽略，抛出异常也不会中止批量解密
+    /// - `progress_callback`: 可选的进度回调，每项处理完（无论成败）后以(已完成项数, 总项数)调用，
+    ///   抛出异常时停止并返回该异常
+    /// 
+    /// # 返回
+    /// 与输入顺序一致的结果数组，成功项为`{ ok: true, data }`，失败项为`{ ok: false, error }`；
+    /// 密钥无效时整体返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_batch(
+        &self,
+        items: &js_sys::Array,
+        key_base64: &str,
+        on_error: Option<js_sys::Function>,
+        progress_callback: Option<js_sys::Function>,
+    ) -> Result<js_sys::Array, JsValue> {
+        let prepared = PreparedKey::create(key_base64).map_err(|e| e.to_js(self.locale))?;
+
+        let total = items.length();
+        let results = js_sys::Array::new();
+        for (index, item) in items.iter().enumerate() {
+            results.push(&self.batch_entry(&prepared, index, &item, on_error.as_ref()));
+            if let Some(callback) = &progress_callback {
+                callback.call2(&JsValue::NULL, &results.length().into(), &total.into())?;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 批量解密的异步版本，按每帧时间预算在项与项之间让出主线程
+    /// 每帧内连续解密若干项，耗时达到预算后等待下一帧（窗口中使用requestAnimationFrame，
+    /// Worker中退回setTimeout）再继续，使大批量解密时进度条等界面能够刷新；每帧至少解密一项。
+    /// 输入格式、回调和结果与decrypt_batch相同
+    /// 
+    /// # 参数
+    /// - `items`: 待解密的项
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `frame_budget_ms`: 每帧最多用于解密的毫秒数，必须是大于0的有限数
+    /// - `on_error`: 可选的失败回调，以(序号, 错误对象)调用，异常被忽略
+    /// - `progress_callback`: 可选的进度回调，以(已完成项数, 总项数)调用，抛出异常时reject
+    /// 
+    /// # 返回
+    /// resolve为结果数组的Promise；密钥无效或预算不合法时reject
+    #[wasm_bindgen]
+    pub fn decrypt_batch_async(
+        &self,
+        items: &js_sys::Array,
+        key_base64: &str,
+        frame_budget_ms: f64,
+        on_error: Option<js_sys::Function>,
+        progress_callback: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let items = items.clone();
+        let key_base64 = key_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let to_js = |e: Error| e.to_js(decryptor.locale);
+
+            if !frame_budget_ms.is_finite() || frame_budget_ms <= 0.0 {
+                return Err(to_js(Error::InvalidFrameBudget(frame_budget_ms)));
+            }
+            let prepared = PreparedKey::create(&key_base64).map_err(to_js)?;
+
+            let total = items.length();
+            let results = js_sys::Array::new();
+            let mut frame_start = now_ms();
+            for (index, item) in items.iter().enumerate() {
+                results.push(&decryptor.batch_entry(&prepared, index, &item, on_error.as_ref()));
+                if let Some(callback) = &progress_callback {
+                    callback.call2(&JsValue::NULL, &results.length().into(), &total.into())?;
+                }
+
+                if results.length() < total && now_ms() - frame_start >= frame_budget_ms {
+                    next_frame().await?;
+                    frame_start = now_ms();
+                }
+            }
+
+            Ok(results.into())
+        })
+    }
+
+    /// 解密CBC密文中按块对齐的一段，用于配合HTTP Range请求只下载部分密文
+    /// CBC中每块只依赖前一个密文块，因此只要多请求该段之前的一块，就能以它作为IV解密该段。
+    /// 该段的起止位置都必须落在16字节边界上；从密文开头解密时，`prev_block`传入原始IV。
+    /// 只有包含密文最后一块的一段才带有PKCS7填充，其余段不移除填充
+    /// 
+    /// # 参数
+    /// - `ciphertext_with_prev_block`: 要解密的密文段（不含前一块），长度必须是16字节的倍数
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `prev_block`: 紧邻该段之前的16字节密文块
+    /// - `is_final`: 该段是否以密文的最后一块结尾，为true时校验并移除填充
+    /// 
+    /// # 返回
+    /// 该段对应的明文，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_aligned_range(
+        &self,
+        ciphertext_with_prev_block: &Uint8Array,
+        key_base64: &str,
+        prev_block: &Uint8Array,
+        is_final: bool,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.decrypt_range(ciphertext_with_prev_block, key_base64, prev_block, is_final);
+        let decrypted = self
+            .track(result, ciphertext_with_prev_block)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 优化的解密图片数据方法
+    /// 提供与decrypt_image相同的功能，但针对渐进式解密进行了优化
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_image_optimized(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        // 使用相同的内部实现，但可以在未来添加优化
+        match self.decrypt_bytes_internal(encrypted_data, key_base64, iv_base64) {
+            Ok(decrypted) => Ok(decrypted),
+            Err(e) => Err(e.to_js(self.locale))
+        }
+    }
+
+    /// 解密并先返回明文长度
+    /// 明文暂存在WASM内存中，调用方可按长度预分配缓冲区后再用copy_result取回
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含length（去除填充后的明文长度）和token字段的对象
+    #[wasm_bindgen]
+    pub fn decrypt_and_measure(
+        &mut self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let token = self.next_token;
+        self.next_token = self.next_token.wrapping_add(1).max(1);
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"length".into(), &(decrypted.len() as u32).into()).unwrap();
+        js_sys::Reflect::set(&result, &"token".into(), &token.into()).unwrap();
+
+        self.pending_results.insert(token, decrypted);
+
+        Ok(result.into())
+    }
+
+    /// 取回decrypt_and_measure暂存的解密结果
+    /// 每个令牌只能使用一次，取回后WASM侧的缓冲区即被释放
+    /// 
+    /// # 参数
+    /// - `token`: decrypt_and_measure返回的令牌
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，令牌无效时返回错误
+    #[wasm_bindgen]
+    pub fn copy_result(&mut self, token: u32) -> Result<Uint8Array, JsValue> {
+        self.pending_results
+            .remove(&token)
+            .map(|decrypted| Uint8Array::from(&decrypted[..]))
+            .ok_or_else(|| Error::InvalidToken(token).to_js(self.locale))
+    }
+
+    /// 解密并返回直接指向WASM内存的视图，省去复制到新Uint8Array的开销
+    /// 只用于对复制开销敏感的场景。返回的视图在以下任一情况发生后失效，不能再读取：
+    /// - 再次调用decrypt_image_view（缓冲区被新结果替换）
+    /// - WASM内存增长（任何方法的内存分配都可能触发，包括其他解密方法），此时视图变为空或指向无关数据
+    /// - 该解密器实例被释放
+    /// 
+    /// 需要长期保留结果时应立即用`slice()`复制出来
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 指向解密结果的Uint8Array视图，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_image_view(
+        &mut self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        self.view_buffer = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        // SAFETY: 视图指向self.view_buffer，调用方须遵守上述失效规则，在缓冲区被替换、
+        // 释放或内存增长之前使用完毕
+        Ok(unsafe { Uint8Array::view(&self.view_buffer) })
+    }
+
+    /// 解密并解码为按行对齐的RGBA像素数据
+    /// 每行末尾补零至`row_alignment`的整数倍，可直接用于WebGL的texImage2D，
+    /// 避免在JS中重新打包像素
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `row_alignment`: 行对齐字节数（如WebGL默认的4），须为不超过256的2的幂
+    /// 
+    /// # 返回
+    /// 包含data、width、height、stride字段的对象，仅支持可解码的位图格式
+    #[wasm_bindgen]
+    pub fn decrypt_to_rgba_aligned(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        row_alignment: u32,
+    ) -> Result<JsValue, JsValue> {
+        raster::check_row_alignment(row_alignment).map_err(|e| e.to_js(self.locale))?;
+
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let (pixels, stride) = raster::to_rgba_aligned(&image, row_alignment).map_err(|e| e.to_js(self.locale))?;
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"data".into(), &Uint8Array::from(&pixels[..])).unwrap();
+        js_sys::Reflect::set(&result, &"width".into(), &image.width().into()).unwrap();
+        js_sys::Reflect::set(&result, &"height".into(), &image.height().into()).unwrap();
+        js_sys::Reflect::set(&result, &"stride".into(), &(stride as u32).into()).unwrap();
+
+        Ok(result.into())
+    }
+
+    /// 解密并解码为预乘Alpha的RGBA像素数据
+    /// 供要求预乘Alpha的WebGL合成使用，免去在JS中逐像素处理；
+    /// 没有Alpha通道的格式（如JPEG）不做任何处理
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含data、width、height字段的对象，仅支持可解码的位图格式
+    #[wasm_bindgen]
+    pub fn decrypt_to_premultiplied_rgba(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let pixels = raster::to_premultiplied_rgba(&image);
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"data".into(), &Uint8Array::from(&pixels[..])).unwrap();
+        js_sys::Reflect::set(&result, &"width".into(), &image.width().into()).unwrap();
+        js_sys::Reflect::set(&result, &"height".into(), &image.height().into()).unwrap();
+
+        Ok(result.into())
+    }
+
+    /// 解密并解码为可用于构造ImageData的RGBA像素数据，同时给出色彩空间
+    /// 图片内嵌的ICC配置文件被启发式识别为Display P3（按原色或已知名称）时colorSpace为"display-p3"，否则（包括没有配置文件）为"srgb"，
+    /// 调用方可据此以`new ImageData(data, width, height, { colorSpace })`构造，避免P3图片颜色发灰
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含data（Uint8ClampedArray）、width、height和colorSpace字段的对象，仅支持可解码的位图格式
+    #[wasm_bindgen]
+    pub fn decrypt_to_image_data(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let (width, height) = (image.width(), image.height());
+        let pixels = image.into_rgba8().into_raw();
+
+        let profile = jpeg::icc_profile(&decrypted).or_else(|| png::icc_profile(&decrypted));
+        let color_space = if profile.is_some_and(|profile| icc::is_p3(&profile)) { "display-p3" } else { "srgb" };
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"data".into(), &js_sys::Uint8ClampedArray::from(&pixels[..])).unwrap();
+        js_sys::Reflect::set(&result, &"width".into(), &width.into()).unwrap();
+        js_sys::Reflect::set(&result, &"height".into(), &height.into()).unwrap();
+        js_sys::Reflect::set(&result, &"colorSpace".into(), &color_space.into()).unwrap();
+
+        Ok(result.into())
+    }
+
+    /// 解密并直接绘制到OffscreenCanvas
+    /// 像素数据始终保留在Worker内，无需以Uint8Array形式传回主线程；
+    /// 画布尺寸与图片不一致时会先调整为图片尺寸
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `canvas`: 目标OffscreenCanvas
+    /// 
+    /// # 返回
+    /// 绘制完成后resolve的Promise，失败时reject错误信息
+    #[wasm_bindgen]
+    pub fn decrypt_to_offscreen(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        canvas: web_sys::OffscreenCanvas,
+    ) -> js_sys::Promise {
+        match self.draw_to_offscreen(encrypted_data, key_base64, iv_base64, &canvas) {
+            Ok(()) => js_sys::Promise::resolve(&JsValue::UNDEFINED),
+            Err(e) => js_sys::Promise::reject(&e),
+        }
+    }
+
+    /// 解密精灵图集并拆分为单独的图片
+    /// 图集由多张图片拼接后整体加密，明文开头为索引表：
+    /// 4字节大端图片数量，随后每张图片为4字节大端偏移和4字节大端长度（偏移从明文起始处计算）
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图集数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 按索引顺序排列的Uint8Array数组，索引损坏或越界时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_sprite_sheet(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<js_sys::Array, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let entries = format::sprite_entries(&decrypted).map_err(|e| e.to_js(self.locale))?;
+
+        let images = js_sys::Array::new();
+        for (offset, len) in entries {
+            images.push(&Uint8Array::from(&decrypted[offset..offset + len]));
+        }
+
+        Ok(images)
+    }
+
+    /// 解密动画WebP并拆分为单独的帧
+    /// 每帧为合成后的完整画面，编码为PNG；静态WebP返回单帧数组
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的WebP数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 帧对象数组，每项包含data（PNG编码的Uint8Array）和duration（显示时长，毫秒）
+    #[wasm_bindgen]
+    pub fn decrypt_webp_frames(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<js_sys::Array, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let frames = raster::webp_frames(&decrypted).map_err(|e| e.to_js(self.locale))?;
+
+        let result = js_sys::Array::new();
+        for (image, duration) in frames {
+            let png = raster::encode(&image, image::ImageFormat::Png).map_err(|e| e.to_js(self.locale))?;
+
+            let frame = js_sys::Object::new();
+            js_sys::Reflect::set(&frame, &"data".into(), &Uint8Array::from(&png[..])).unwrap();
+            js_sys::Reflect::set(&frame, &"duration".into(), &duration.into()).unwrap();
+            result.push(&frame);
+        }
+
+        Ok(result)
+    }
+
+    /// 解密并切分为固定尺寸的瓦片
+    /// 先完整解码图片，再按行优先顺序切分，每个瓦片编码为PNG；右侧和底部边缘的瓦片可能较小
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `tile_size`: 瓦片边长（像素）
+    /// 
+    /// # 返回
+    /// 瓦片对象数组，每项包含x、y（瓦片左上角的像素坐标）和data（PNG编码的Uint8Array）
+    #[wasm_bindgen]
+    pub fn decrypt_to_tiles(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        tile_size: u32,
+    ) -> Result<js_sys::Array, JsValue> {
+        if tile_size == 0 {
+            return Err(Error::ZeroTileSize.to_js(self.locale));
+        }
+
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+
+        let result = js_sys::Array::new();
+        for (x, y, tile) in raster::tiles(&image, tile_size) {
+            let png = raster::encode(&tile, image::ImageFormat::Png).map_err(|e| e.to_js(self.locale))?;
+
+            let entry = js_sys::Object::new();
+            js_sys::Reflect::set(&entry, &"x".into(), &x.into()).unwrap();
+            js_sys::Reflect::set(&entry, &"y".into(), &y.into()).unwrap();
+            js_sys::Reflect::set(&entry, &"data".into(), &Uint8Array::from(&png[..])).unwrap();
+            result.push(&entry);
+        }
+
+        Ok(result)
+    }
+
+    /// 解密并解码为按WebGPU行对齐的RGBA像素，可选直接写入GPUBuffer
+    /// 每行填充至256字节的整数倍，满足`copyBufferToTexture`对bytesPerRow的要求。
+    /// 由于web-sys的WebGPU绑定仍不稳定，queue和buffer按JS对象传入，通过`queue.writeBuffer(buffer, 0, data)`写入
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `queue`: GPUQueue（通常为`device.queue`），仅需像素数据时传undefined
+    /// - `buffer`: 目标GPUBuffer，需带COPY_DST用途且容量不小于填充后的数据长度，可传undefined
+    /// 
+    /// # 返回
+    /// Promise，解析为包含data、width、height、bytesPerRow字段的对象；
+    /// 同时传入queue和buffer时，解析前已完成写入
+    #[wasm_bindgen]
+    pub fn decrypt_to_gpu_buffer(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        queue: JsValue,
+        buffer: JsValue,
+    ) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let encrypted_data = encrypted_data.clone();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let decrypted = decryptor
+                .decrypt_to_vec(&encrypted_data, &key_base64, &iv_base64)
+                .map_err(|e| e.to_js(decryptor.locale))?;
+            let image = raster::decode(&decrypted).map_err(|e| e.to_js(decryptor.locale))?;
+            let (pixels, bytes_per_row) = raster::to_rgba_aligned(&image, raster::MAX_ROW_ALIGNMENT)
+                .map_err(|e| e.to_js(decryptor.locale))?;
+            let data = Uint8Array::from(&pixels[..]);
+
+            if queue.is_object() && buffer.is_object() {
+                let write_buffer: js_sys::Function = js_sys::Reflect::get(&queue, &"writeBuffer".into())?.dyn_into()?;
+                write_buffer.call3(&queue, &buffer, &0.into(), &data)?;
+            }
+
+            let result = js_sys::Object::new();
+            js_sys::Reflect::set(&result, &"data".into(), &data).unwrap();
+            js_sys::Reflect::set(&result, &"width".into(), &image.width().into()).unwrap();
+            js_sys::Reflect::set(&result, &"height".into(), &image.height().into()).unwrap();
+            js_sys::Reflect::set(&result, &"bytesPerRow".into(), &(bytes_per_row as u32).into()).unwrap();
+
+            Ok(result.into())
+        })
+    }
+
+    /// 解密并计算图片的主色（平均颜色），用作加载时的占位背景色
+    /// 解码后缩小为16×16以内的缩略图再按Alpha加权平均，开销远小于在JS中用Canvas采样
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含r、g、b字段（0~255）的对象，仅支持可解码的位图格式
+    #[wasm_bindgen]
+    pub fn decrypt_to_dominant_color(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<JsValue, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let [r, g, b] = raster::average_color(&image);
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"r".into(), &r.into()).unwrap();
+        js_sys::Reflect::set(&result, &"g".into(), &g.into()).unwrap();
+        js_sys::Reflect::set(&result, &"b".into(), &b.into()).unwrap();
+
+        Ok(result.into())
+    }
+
+    /// 解密并计算感知哈希，用于相似图片去重
+    /// 两种哈希都先缩放为灰度小图，结果为64位，以16位十六进制表示；比较时按汉明距离判断相似度
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `algorithm`: "ahash"（均值哈希，默认）或"dhash"（差值哈希）
+    /// 
+    /// # 返回
+    /// 十六进制哈希；图片无法解码或算法不支持时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_to_phash(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        algorithm: Option<String>,
+    ) -> Result<String, JsValue> {
+        let algorithm = algorithm.unwrap_or_else(|| "ahash".to_string());
+        let hasher: fn(&image::DynamicImage) -> u64 = match algorithm.to_ascii_lowercase().as_str() {
+            "ahash" => raster::average_hash,
+            "dhash" => raster::difference_hash,
+            _ => return Err(Error::UnsupportedHashAlgorithm(algorithm).to_js(self.locale)),
+        };
+
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(hash::to_hex(&hasher(&image).to_be_bytes()))
+    }
+
+    /// 解密后重新编码为目标格式
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `target`: 目标格式，"png"、"jpeg"、"webp"，启用`avif`特性后可用"avif"
+    /// - `quality`: 编码质量（1~100），仅对JPEG和AVIF生效
+    /// 
+    /// # 返回
+    /// 重新编码后的图片数据；源图片无法解码或目标格式不支持时返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_and_transcode(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        target: &str,
+        quality: u8,
+    ) -> Result<Uint8Array, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let encoded = raster::transcode(&image, target, quality).map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&encoded[..]))
+    }
+
+    /// 检查密文在给定密钥下能否正确移除PKCS7填充，不返回明文
+    /// 明文只在WASM内部短暂存在，返回前会被清零。
+    /// 注意：若把该结果暴露给不可信的一方，可构成填充预言攻击（padding oracle），
+    /// 攻击者可借此逐字节恢复明文；仅应用于本地的密钥匹配等场景
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 填充有效时返回true；填充无效或输入不合法（如密钥格式错误）时返回false
+    #[wasm_bindgen]
+    pub fn check_padding(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> bool {
+        let Ok(mut plaintext) = self.decrypt_cbc_blocks(encrypted_data, key_base64, iv_base64, self.iv_mode) else {
+            return false;
+        };
+
+        let valid = padding::pkcs7_unpadded_len_for(&plaintext, self.pad_block_size).is_ok();
+        plaintext.zeroize();
+
+        valid
+    }
+
+    /// 只解密第一个密文块，用于与参考实现逐字节比对
+    /// 返回该块经AES解密并与IV异或后的16字节，不移除填充，也不解密后续块，
+    /// 便于判断密钥、IV和数据中哪一项有误。IV来源遵循当前的IV模式
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据，至少16字节
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 第一个块的16字节明文，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_first_block(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        self.first_block(encrypted_data, key_base64, iv_base64)
+            .map(|block| Uint8Array::from(&block[..]))
+            .map_err(|e| e.to_js(self.locale))
+    }
+
+    /// 试运行解密流程，只解密开头几块和最后一块
+    /// 在完整解密大文件之前快速检查密钥、IV、填充和图片格式是否正确。
+    /// 填充有效且文件头可识别时很可能成功，但无法发现中间数据损坏等问题
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 包含willLikelySucceed、detectedType（无法识别时为null）和issues（问题描述数组）字段的对象
+    #[wasm_bindgen]
+    pub fn dry_run(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> JsValue {
+        let (detected, issues) = match self.dry_run_checks(encrypted_data, key_base64, iv_base64) {
+            Ok(checks) => checks,
+            Err(e) => (String::new(), vec![e]),
+        };
+
+        let issue_list = js_sys::Array::new();
+        for issue in &issues {
+            issue_list.push(&issue.message(self.locale).into());
+        }
+
+        let detected_type: JsValue = if detected.is_empty() { JsValue::NULL } else { detected.into() };
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"willLikelySucceed".into(), &issues.is_empty().into()).unwrap();
+        js_sys::Reflect::set(&result, &"detectedType".into(), &detected_type).unwrap();
+        js_sys::Reflect::set(&result, &"issues".into(), &issue_list).unwrap();
+
+        result.into()
+    }
+
+    /// 解密为按需读取的ReadableStream
+    /// 每次读取时才解密下一段密文（每段64KB），无需一次性生成完整明文；
+    /// 最后一段为移除填充后的尾部。输入校验在创建时完成，解密中途出错时流进入错误状态
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 输出明文Uint8Array分段的ReadableStream
+    #[wasm_bindgen]
+    pub fn decrypt_to_stream(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<web_sys::ReadableStream, JsValue> {
+        let (decryptor, ciphertext) = self
+            .incremental_decryptor(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let tracker = self.detached();
+        let encrypted_data = encrypted_data.clone();
+        let mut decryptor = Some(decryptor);
+        let mut offset = 0u32;
+
+        let pull = Closure::<dyn FnMut(web_sys::ReadableStreamDefaultController)>::new(
+            move |controller: web_sys::ReadableStreamDefaultController| {
+                let Some(active) = decryptor.as_mut() else { return };
+
+                // 不足一块的数据不会产生输出，继续读取直到有明文可以输出或到达末尾
+                while offset < ciphertext.length() {
+                    let end = ciphertext.length().min(offset.saturating_add(STREAM_CHUNK_SIZE));
+                    let chunk = ciphertext.subarray(offset, end).to_vec();
+                    offset = end;
+
+                    match active.feed(&chunk) {
+                        Ok(plaintext) if plaintext.is_empty() => continue,
+                        Ok(plaintext) => {
+                            let _ = controller.enqueue_with_chunk(&Uint8Array::from(&plaintext[..]));
+                            return;
+                        }
+                        Err(e) => {
+                            decryptor = None;
+                            if let Err(e) = tracker.track(Err(e), &encrypted_data) {
+                                controller.error_with_e(&e.to_js(tracker.locale));
+                            }
+                            return;
+                        }
+                    }
+                }
+
+                let Some(active) = decryptor.take() else { return };
+                match tracker.track(active.finish(), &encrypted_data) {
+                    Ok(tail) => {
+                        if !tail.is_empty() {
+                            let _ = controller.enqueue_with_chunk(&Uint8Array::from(&tail[..]));
+                        }
+                        let _ = controller.close();
+                    }
+                    Err(e) => controller.error_with_e(&e.to_js(tracker.locale)),
+                }
+            },
+        );
+
+        let source = js_sys::Object::new();
+        js_sys::Reflect::set(&source, &"pull".into(), &pull.into_js_value()).unwrap();
+
+        web_sys::ReadableStream::new_with_underlying_source(&source)
+    }
+
+    /// 创建解密用的TransformStream，可直接接在fetch响应之后：
+    /// `response.body.pipeThrough(decryptor.create_decrypt_transform(key, iv))`
+    /// 输入分段无需按16字节对齐，跨分段的不完整块会缓存到下一段；
+    /// 最后一个密文块保留到流结束时才解密并移除PKCS7填充。
+    /// 总是使用传入的IV，不受IV模式设置影响；解密出错或累计输入超过最大限制时流进入错误状态
+    /// 
+    /// # 参数
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 输入密文字节、输出明文Uint8Array分段的TransformStream
+    #[wasm_bindgen]
+    pub fn create_decrypt_transform(&self, key_base64: &str, iv_base64: &str) -> Result<web_sys::TransformStream, JsValue> {
+        let to_js = |e: Error| e.to_js(self.locale);
+
+        if key_base64.is_empty() {
+            return Err(to_js(Error::EmptyKey));
+        }
+        if iv_base64.is_empty() {
+            return Err(to_js(Error::EmptyIv));
+        }
+        Self::check_key_iv_order(key_base64, iv_base64).map_err(to_js)?;
+
+        let key = Self::decode_key(key_base64).map_err(to_js)?;
+        let iv = self.decode_iv_with_policy(iv_base64).map_err(to_js)?;
+
+        // transform和flush共享同一个解密器，flush时取出并结束
+        let decryptor = Rc::new(RefCell::new(Some(self.new_incremental(key, iv))));
+        let received = Rc::new(Cell::new(0usize));
+
+        let transform = {
+            let decryptor = Rc::clone(&decryptor);
+            let received = Rc::clone(&received);
+            let tracker = self.detached();
+            Closure::<dyn FnMut(JsValue, web_sys::TransformStreamDefaultController) -> Result<(), JsValue>>::new(
+                move |chunk: JsValue, controller: web_sys::TransformStreamDefaultController| {
+                    let chunk = Uint8Array::new(&chunk);
+                    received.set(received.get().saturating_add(chunk.length() as usize));
+
+                    let mut active = decryptor.borrow_mut();
+                    // 累计输入超过最大限制时丢弃解密器，流进入错误状态
+                    if let Err(e) = tracker.check_input_len(received.get()) {
+                        active.take();
+                        return Err(e.to_js(tracker.locale));
+                    }
+                    let chunk = chunk.to_vec();
+                    let Some(active) = active.as_mut() else { return Ok(()) };
+                    let plaintext = active.feed(&chunk).map_err(|e| e.to_js(tracker.locale))?;
+                    if !plaintext.is_empty() {
+                        controller.enqueue_with_chunk(&Uint8Array::from(&plaintext[..]))?;
+                    }
+                    Ok(())
+                },
+            )
+        };
+
+        let flush = {
+            let tracker = self.detached();
+            Closure::<dyn FnMut(web_sys::TransformStreamDefaultController) -> Result<(), JsValue>>::new(
+                move |controller: web_sys::TransformStreamDefaultController| {
+                    let Some(active) = decryptor.borrow_mut().take() else { return Ok(()) };
+                    let tail = tracker
+                        .track_len(active.finish(), received.get())
+                        .map_err(|e| e.to_js(tracker.locale))?;
+                    if !tail.is_empty() {
+                        controller.enqueue_with_chunk(&Uint8Array::from(&tail[..]))?;
+                    }
+                    Ok(())
+                },
+            )
+        };
+
+        let transformer = js_sys::Object::new();
+        js_sys::Reflect::set(&transformer, &"transform".into(), &transform.into_js_value()).unwrap();
+        js_sys::Reflect::set(&transformer, &"flush".into(), &flush.into_js_value()).unwrap();
+
+        web_sys::TransformStream::new_with_transformer(&transformer)
+    }
+
+    /// 分段解密，每段明文产生后立即交给回调
+    /// 每段密文为64KB，回调依次收到各段明文的Uint8Array，最后一次调用为移除填充后的尾部
+    /// （可能为空）。回调抛出异常时停止解密，Promise以该异常reject
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `chunk_callback`: 可选的分段回调
+    /// 
+    /// # 返回
+    /// resolve为完整解密结果（Uint8Array）的Promise，失败时reject错误信息
+    #[wasm_bindgen]
+    pub fn decrypt_chunked(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        chunk_callback: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let encrypted_data = encrypted_data.clone();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            // 保留回调抛出的原始异常，用于reject
+            let mut thrown = None;
+            let result = decryptor.decrypt_in_chunks(&encrypted_data, &key_base64, &iv_base64, |part| {
+                let Some(callback) = &chunk_callback else { return Ok(()) };
+                callback.call1(&JsValue::NULL, &Uint8Array::from(part)).map_err(|e| {
+                    thrown = Some(e);
+                    Error::ChunkCallback
+                })?;
+                Ok(())
+            });
+
+            match decryptor.track(result, &encrypted_data) {
+                Ok(decrypted) => Ok(Uint8Array::from(&decrypted[..]).into()),
+                Err(e) => Err(thrown.unwrap_or_else(|| e.to_js(decryptor.locale))),
+            }
+        })
+    }
+
+    /// 按每帧时间预算分段解密，避免长时间占用主线程
+    /// 每帧内连续解密64KB的分段，直到耗时达到预算后等待下一帧
+    /// （窗口中使用requestAnimationFrame，Worker中退回setTimeout）再继续；
+    /// 每帧至少解密一段，因此预算很小时也能推进。总工作量与一次性解密相同
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `frame_budget_ms`: 每帧最多用于解密的毫秒数，如60fps下可取8；必须是大于0的有限数
+    /// - `progress_callback`: 可选的进度回调，每帧结束时以(已处理密文字节数, 密文总字节数)调用
+    /// 
+    /// # 返回
+    /// resolve为完整解密结果（Uint8Array）的Promise；解密失败或回调抛出异常时reject
+    #[wasm_bindgen]
+    pub fn decrypt_time_budgeted(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        frame_budget_ms: f64,
+        progress_callback: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let encrypted_data = encrypted_data.clone();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let result = decryptor
+                .decrypt_budgeted(&encrypted_data, &key_base64, &iv_base64, frame_budget_ms, progress_callback.as_ref())
+                .await;
+            let decrypted = decryptor.track_len(result, encrypted_data.length() as usize)?;
+
+            Ok(Uint8Array::from(&decrypted[..]).into())
+        })
+    }
+
+    /// 在浏览器空闲时段分段解密，适合不急于显示的后台导入
+    /// 每个64KB分段都通过requestIdleCallback调度，空闲期限内还有剩余时间时继续解密下一段；
+    /// 不支持requestIdleCallback的环境（如Worker）退回setTimeout，每次只解密一段
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// resolve为完整解密结果（Uint8Array）的Promise，解密失败时reject
+    #[wasm_bindgen]
+    pub fn decrypt_image_idle(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let encrypted_data = encrypted_data.clone();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let result = decryptor.decrypt_idle(&encrypted_data, &key_base64, &iv_base64).await;
+            let decrypted = decryptor.track_len(result, encrypted_data.length() as usize)?;
+
+            Ok(Uint8Array::from(&decrypted[..]).into())
+        })
+    }
+
+    /// 解密并直接写入SharedArrayBuffer的指定偏移处
+    /// 便于多个Worker共享解密结果，无需再复制出WASM内存后转发。
+    /// 使用SharedArrayBuffer要求页面启用跨源隔离，即响应头需包含
+    /// `Cross-Origin-Opener-Policy: same-origin`和`Cross-Origin-Embedder-Policy: require-corp`
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `shared`: 目标SharedArrayBuffer
+    /// - `offset`: 写入位置（字节），按字节写入因此无对齐要求
+    /// 
+    /// # 返回
+    /// 写入的字节数，剩余容量不足时返回错误且不写入任何数据
+    #[wasm_bindgen]
+    pub fn decrypt_into_shared(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        shared: &js_sys::SharedArrayBuffer,
+        offset: usize,
+    ) -> Result<usize, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let capacity = shared.byte_length() as usize;
+        let fits = offset
+            .checked_add(decrypted.len())
+            .is_some_and(|end| end <= capacity);
+        if !fits {
+            return Err(Error::InsufficientCapacity {
+                needed: decrypted.len(),
+                offset,
+                capacity,
+            }
+            .to_js(self.locale));
+        }
+
+        let target = Uint8Array::new_with_byte_offset_and_length(shared, offset as u32, decrypted.len() as u32);
+        target.copy_from(&decrypted);
+
+        Ok(decrypted.len())
+    }
+
+    /// 解密Blob/File中的数据
+    /// 内部等待`blob.arrayBuffer()`读取完成后再解密，省去调用方的异步读取步骤；
+    /// Blob大小超过最大输入限制时不读取，直接reject
+    /// 
+    /// # 参数
+    /// - `blob`: 包含加密数据的Blob或File
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// resolve为解密后图片数据（Uint8Array）的Promise，失败时reject错误信息
+    #[wasm_bindgen]
+    pub fn decrypt_blob(&self, blob: web_sys::Blob, key_base64: &str, iv_base64: &str) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            // 读入内存之前先按Blob大小检查最大输入限制
+            decryptor
+                .check_input_len(blob.size() as usize)
+                .map_err(|e| e.to_js(decryptor.locale))?;
+
+            let buffer = wasm_bindgen_futures::JsFuture::from(blob.array_buffer()).await?;
+            let encrypted_data = Uint8Array::new(&buffer);
+
+            decryptor
+                .decrypt_bytes_internal(&encrypted_data, &key_base64, &iv_base64)
+                .map(JsValue::from)
+                .map_err(|e| e.to_js(decryptor.locale))
+        })
+    }
+
+    /// 解密File System Access API文件句柄指向的文件
+    /// 不超过8MB的文件一次性读取后解密；更大的文件按64KB分段读取并增量解密，
+    /// 无需把完整密文读入内存
+    /// 
+    /// 读取某一段失败属于可恢复错误：传入`on_recoverable_error`时，回调收到
+    /// `{ offset, end, attempt, error }`，返回真值则重新读取该段，返回假值或抛出异常则中止。
+    /// 密钥、IV和填充错误属于致命错误，不经过回调，直接reject
+    /// 
+    /// # 参数
+    /// - `handle`: 加密文件的FileSystemFileHandle
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// - `on_recoverable_error`: 可选的可恢复错误回调，不传时读取失败立即reject
+    /// 
+    /// # 返回
+    /// resolve为解密后图片数据（Uint8Array）的Promise，读取或解密失败时reject错误信息
+    #[wasm_bindgen]
+    pub fn decrypt_file_handle(
+        &self,
+        handle: web_sys::FileSystemFileHandle,
+        key_base64: &str,
+        iv_base64: &str,
+        on_recoverable_error: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let decryptor = self.detached();
+        let key_base64 = key_base64.to_string();
+        let iv_base64 = iv_base64.to_string();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let file: web_sys::Blob = wasm_bindgen_futures::JsFuture::from(handle.get_file()).await?.dyn_into()?;
+            let len = file.size() as usize;
+            decryptor.check_input_len(len).map_err(|e| e.to_js(decryptor.locale))?;
+
+            let on_recoverable_error = on_recoverable_error.as_ref();
+            if len <= FILE_STREAM_THRESHOLD {
+                let buffer = read_blob_range_retrying(&file, 0, len, on_recoverable_error).await?;
+                return decryptor
+                    .decrypt_bytes_internal(&Uint8Array::from(&buffer[..]), &key_base64, &iv_base64)
+                    .map(JsValue::from)
+                    .map_err(|e| e.to_js(decryptor.locale));
+            }
+
+            let result = decryptor
+                .decrypt_blob_in_chunks(&file, len, &key_base64, &iv_base64, on_recoverable_error)
+                .await;
+            let decrypted = decryptor.track_len(result, len)?;
+
+            Ok(Uint8Array::from(&decrypted[..]).into())
+        })
+    }
+
+    /// 解密并通过浏览器的createImageBitmap解码为ImageBitmap
+    /// 解密在WASM内完成，图片解码交给浏览器的优化路径；
+    /// 直接以Blob调用createImageBitmap，不创建需要回收的Blob URL
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// resolve为ImageBitmap的Promise，失败时reject错误信息
+    #[wasm_bindgen]
+    pub fn decrypt_to_image_bitmap(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> js_sys::Promise {
+        match self.create_image_bitmap(encrypted_data, key_base64, iv_base64) {
+            Ok(promise) => promise,
+            Err(e) => js_sys::Promise::reject(&e),
+        }
+    }
+
+    /// 根据密文前缀快速探测图片类型
+    /// 只解密已到达数据中按16字节对齐的部分且不移除填充，
+    /// 适合在下载首个分块后尽早放弃无效文件
+    /// 
+    /// # 参数
+    /// - `partial_ciphertext`: 加密数据的前缀（如前1MB）
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: Base64编码的16字节初始化向量
+    /// 
+    /// # 返回
+    /// 检测到的MIME类型，无法识别时返回空字符串
+    #[wasm_bindgen]
+    pub fn peek_type(&self, partial_ciphertext: &Uint8Array, key_base64: &str, iv_base64: &str) -> JsValue {
+        let (cipher, ciphertext) = match self.prepare_cipher(partial_ciphertext, key_base64, iv_base64, self.iv_mode) {
+            Ok(prepared) => prepared,
+            Err(_) => return JsValue::from_str(""),
+        };
+
+        // 只取按块对齐的前缀，CBC解密前缀不依赖后续数据
+        let aligned_len = ciphertext.length() as usize / 16 * 16;
+        if aligned_len == 0 {
+            return JsValue::from_str("");
+        }
+
+        let mut buffer = ciphertext.subarray(0, aligned_len as u32).to_vec();
+        let prefix = match cipher.decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer) {
+            Ok(prefix) => prefix,
+            Err(_) => return JsValue::from_str(""),
+        };
+
+        JsValue::from_str(&self.detect_image_type(prefix))
+    }
+
+    /// 检测图片格式
+    /// 与verify_decrypted_image中的fileType使用相同的检测规则（包括自定义签名），
+    /// 但返回枚举而不是MIME字符串
+    /// 
+    /// # 参数
+    /// - `data`: 解密后的数据
+    /// 
+    /// # 返回
+    /// 检测到的格式，无法识别时为`ImageFormat.Unknown`
+    #[wasm_bindgen]
+    pub fn detect_format(&self, data: &Uint8Array) -> ImageFormat {
+        ImageFormat::from_mime(&self.detect_image_type(&data.to_vec()))
+    }
+
+    /// 验证解密结果
+    /// 开启文件尾检查后额外报告headerValid和footerValid：文件头正确但文件尾不符，
+    /// 通常说明图片被截断。支持JPEG（EOI）、PNG（IEND块）、GIF（结束符）和WebP（RIFF长度），
+    /// 其他格式不报告footerValid
+    /// 
+    /// # 参数
+    /// - `decrypted_data`: 解密后的数据
+    /// - `verify_footer`: 是否检查文件尾，默认为false
+    /// 
+    /// # 返回
+    /// 验证结果和文件类型信息
+    #[wasm_bindgen]
+    pub fn verify_decrypted_image(&self, decrypted_data: &Uint8Array, verify_footer: Option<bool>) -> JsValue {
+        let data = decrypted_data.to_vec();
+        
+        if data.len() < 8 {
+            return js_sys::JSON::stringify(&js_sys::Object::new()).unwrap().into();
+        }
+
+        let result = js_sys::Object::new();
+        
+        // 检测文件类型
+        let file_type = self.detect_image_type(&data);
+
+        // 被误标为图片的Lottie动画JSON，单独标记以便交给Lottie播放器，但不算作有效图片
+        let is_lottie = file_type.is_empty() && format::looks_like_lottie(&data);
+        let reported_type = if is_lottie { "application/json" } else { file_type.as_str() };
+        js_sys::Reflect::set(&result, &"fileType".into(), &JsValue::from_str(reported_type)).unwrap();
+        js_sys::Reflect::set(&result, &"isLottie".into(), &is_lottie.into()).unwrap();
+        
+        // 验证文件头
+        let is_valid = !file_type.is_empty();
+        js_sys::Reflect::set(&result, &"isValid".into(), &is_valid.into()).unwrap();
+        
+        // 文件尾（仅在要求时检查）
+        if verify_footer.unwrap_or(false) {
+            js_sys::Reflect::set(&result, &"headerValid".into(), &is_valid.into()).unwrap();
+            if let Some(footer_valid) = footer_valid(&file_type, &data) {
+                js_sys::Reflect::set(&result, &"footerValid".into(), &footer_valid.into()).unwrap();
+            }
+        }
+        
+        // 文件大小
+        js_sys::Reflect::set(&result, &"fileSize".into(), &(data.len() as u32).into()).unwrap();
+
+        // 图片尺寸（仅在可从文件头解析时提供）
+        if let Some((width, height)) = header_dimensions(&data) {
+            js_sys::Reflect::set(&result, &"width".into(), &width.into()).unwrap();
+            js_sys::Reflect::set(&result, &"height".into(), &height.into()).unwrap();
+        }
+
+        // 位深、通道数和颜色类型（仅PNG和JPEG）
+        if let Some(pixel) = header_pixel_format(&data) {
+            js_sys::Reflect::set(&result, &"bitDepth".into(), &pixel.bit_depth.into()).unwrap();
+            js_sys::Reflect::set(&result, &"channels".into(), &pixel.channels.into()).unwrap();
+            js_sys::Reflect::set(&result, &"colorType".into(), &pixel.color_type.into()).unwrap();
+        }
+
+        // JPEG是否为渐进式（以第一个SOF标记为准），其他格式不提供
+        if let Some(info) = jpeg::inspect(&data) {
+            js_sys::Reflect::set(&result, &"progressive".into(), &info.progressive.into()).unwrap();
+        }
+
+        // 无法识别文件头且字节熵接近最大值时，数据很可能仍是密文
+        let likely_encrypted = !is_valid && format::looks_like_ciphertext(&data);
+        js_sys::Reflect::set(&result, &"likelyEncrypted".into(), &likely_encrypted.into()).unwrap();
+        
+        result.into()
+    }
+
+    /// 验证图片能否完整解码
+    /// 与verify_decrypted_image的文件头检查不同，这里会执行完整解码，
+    /// 可以发现文件头正确但数据被截断的图片，开销也相应更大
+    /// 
+    /// # 参数
+    /// - `data`: 解密后的数据
+    /// 
+    /// # 返回
+    /// 包含decodable、width、height、error字段的对象
+    #[wasm_bindgen]
+    pub fn verify_decodable(&self, data: &Uint8Array) -> JsValue {
+        let result = js_sys::Object::new();
+
+        match raster::decode(&data.to_vec()) {
+            Ok(image) => {
+                js_sys::Reflect::set(&result, &"decodable".into(), &true.into()).unwrap();
+                js_sys::Reflect::set(&result, &"width".into(), &image.width().into()).unwrap();
+                js_sys::Reflect::set(&result, &"height".into(), &image.height().into()).unwrap();
+                js_sys::Reflect::set(&result, &"error".into(), &JsValue::NULL).unwrap();
+            }
+            Err(e) => {
+                js_sys::Reflect::set(&result, &"decodable".into(), &false.into()).unwrap();
+                js_sys::Reflect::set(&result, &"width".into(), &0.into()).unwrap();
+                js_sys::Reflect::set(&result, &"height".into(), &0.into()).unwrap();
+                js_sys::Reflect::set(&result, &"error".into(), &e.to_js(self.locale)).unwrap();
+            }
+        }
+
+        result.into()
+    }
+
+    /// 获取解密统计
+    /// 
+    /// # 返回
+    /// 包含decryptCount（成功次数）、failureCount（失败次数）、
+    /// bytesProcessed（成功解密的密文总字节数）字段的对象
+    #[wasm_bindgen]
+    pub fn get_stats(&self) -> JsValue {
+        let stats = js_sys::Object::new();
+
+        js_sys::Reflect::set(&stats, &"decryptCount".into(), &self.stats.decrypt_count.get().into()).unwrap();
+        js_sys::Reflect::set(&stats, &"failureCount".into(), &self.stats.failure_count.get().into()).unwrap();
+        js_sys::Reflect::set(&stats, &"bytesProcessed".into(), &(self.stats.bytes_processed.get() as f64).into()).unwrap();
+
+        stats.into()
+    }
+
+    /// 清零解密统计
+    #[wasm_bindgen]
+    pub fn reset_stats(&self) {
+        self.stats.decrypt_count.set(0);
+        self.stats.failure_count.set(0);
+        self.stats.bytes_processed.set(0);
+        self.stats.last_blocks.set(0);
+    }
+
+    /// 获取上一次解密处理的16字节块数
+    /// 按输入数据长度向上取整计算（CTR等流式模式的末尾不完整块也计为一块），
+    /// 包含FirstBlock模式下的IV块和GCM的nonce与认证标签；上一次解密失败或尚未解密时返回0
+    #[wasm_bindgen]
+    pub fn last_operation_blocks(&self) -> u32 {
+        self.stats.last_blocks.get()
+    }
+
+    /// 获取性能统计信息
+    #[wasm_bindgen]
+    pub fn get_performance_info(&self) -> JsValue {
+        let info = js_sys::Object::new();
+        
+        // WASM特性检测
+        js_sys::Reflect::set(&info, &"wasmSupported".into(), &true.into()).unwrap();
+        
+        // 内存使用情况（简化实现）
+        let memory_pages = wasm_bindgen::memory()
+            .dyn_into::<js_sys::WebAssembly::Memory>()
+            .ok()
+            .and_then(|mem| mem.buffer().dyn_into::<js_sys::ArrayBuffer>().ok())
+            .map(|buf| buf.byte_length() / (64 * 1024))
+            .unwrap_or(0);
+
+        js_sys::Reflect::set(&info, &"memoryPages".into(), &memory_pages.into()).unwrap();
+        
+        info.into()
+    }
+}
+
+#[cfg(feature = "legacy_compat")]
+#[wasm_bindgen]
+impl ImageDecryptor {
+    /// 解密旧版加密器产生的数据
+    /// 旧版加密器存在缺陷：实际使用的IV是存储的IV加1（按128位大端整数，溢出时回绕）。
+    /// 此方法先按同样方式递增IV再解密，用于读取历史数据，新数据请使用decrypt_image
+    /// 
+    /// # 参数
+    /// - `encrypted_data`: 加密的图片数据
+    /// - `key_base64`: Base64编码的32字节密钥
+    /// - `iv_base64`: 旧版加密器存储的Base64编码16字节初始化向量
+    /// 
+    /// # 返回
+    /// 解密后的图片数据，如果失败则返回错误
+    #[wasm_bindgen]
+    pub fn decrypt_legacy_iv_bug(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let result = self.legacy_iv(iv_base64).and_then(|iv| {
+            let result = self.decrypt_legacy(encrypted_data, key_base64, &iv);
+            self.track(result, encrypted_data)
+        });
+        let decrypted = result.map_err(|e| e.to_js(self.locale))?;
+
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+}
+
+#[cfg(feature = "legacy_compat")]
+impl ImageDecryptor {
+    /// 按旧版加密器的方式将IV作为128位大端整数加1
+    fn legacy_iv(&self, iv_base64: &str) -> Result<[u8; 16], Error> {
+        if iv_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        let iv: [u8; 16] = self
+            .decode_iv_with_policy(iv_base64)?
+            .try_into()
+            .map_err(|iv: Vec<u8>| Error::IvLength(iv.len()))?;
+        let shifted = u128::from_be_bytes(iv).wrapping_add(1);
+
+        Ok(shifted.to_be_bytes())
+    }
+
+    /// 用已递增的IV字节解密，密钥按Base64解码
+    fn decrypt_legacy(&self, encrypted_data: &Uint8Array, key_base64: &str, iv: &[u8; 16]) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        let key = Self::decode_key(key_base64)?;
+        self.decrypt_cbc_raw(encrypted_data, &key, iv)
+    }
+}
+
+impl Default for ImageDecryptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageDecryptor {
+    /// 按配置创建实例
+    fn from_config(config: &DecryptorConfig) -> ImageDecryptor {
+        #[cfg(feature = "console_error_panic_hook")]
+        set_panic_hook();
+        
+        ImageDecryptor {
+            pending_results: HashMap::new(),
+            next_token: 1,
+            view_buffer: Vec::new(),
+            max_input_size: config.max_input_size,
+            locale: config.locale,
+            iv_mode: config.iv_mode,
+            strict_iv: config.strict_iv,
+            trim_trailing_newline: config.trim_trailing_newline,
+            pad_block_size: config.pad_block_size,
+            zero_iv_warned: Cell::new(false),
+            stats: Rc::new(DecryptStats::default()),
+            custom_magic: Vec::new(),
+        }
+    }
+
+    /// 创建只包含解密配置的副本
+    /// 异步方法需要在'static的Future中使用配置，不能借用self
+    fn detached(&self) -> ImageDecryptor {
+        ImageDecryptor {
+            pending_results: HashMap::new(),
+            next_token: 1,
+            view_buffer: Vec::new(),
+            max_input_size: self.max_input_size,
+            locale: self.locale,
+            iv_mode: self.iv_mode,
+            strict_iv: self.strict_iv,
+            trim_trailing_newline: self.trim_trailing_newline,
+            pad_block_size: self.pad_block_size,
+            zero_iv_warned: Cell::new(self.zero_iv_warned.get()),
+            stats: Rc::clone(&self.stats),
+            custom_magic: self.custom_magic.clone(),
+        }
+    }
+
+    /// 内部解密方法 - 统一的高性能解密实现
+    /// 减少数据复制，提高性能，统一错误处理
+    fn decrypt_bytes_internal(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> Result<Uint8Array, Error> {
+        let decrypted = self.decrypt_to_vec(encrypted_data, key_base64, iv_base64)?;
+
+        // 直接创建Uint8Array，避免额外的to_vec()调用
+        Ok(Uint8Array::from(&decrypted[..]))
+    }
+
+    /// 解密为Rust字节数组，供需要在WASM内继续处理明文的方法使用
+    fn decrypt_to_vec(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> Result<Vec<u8>, Error> {
+        self.decrypt_with_iv_mode(encrypted_data, key_base64, iv_base64, self.iv_mode)
+    }
+
+    /// 按指定的IV模式解密为Rust字节数组，并记录解密统计
+    fn decrypt_with_iv_mode(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        iv_mode: IvMode,
+    ) -> Result<Vec<u8>, Error> {
+        let result = self.decrypt_cbc(encrypted_data, key_base64, iv_base64, iv_mode);
+        self.track(result, encrypted_data)
+    }
+
+    /// 根据解密结果更新统计
+    fn track(&self, result: Result<Vec<u8>, Error>, encrypted_data: &Uint8Array) -> Result<Vec<u8>, Error> {
+        self.track_len(result, encrypted_data.length() as usize)
+    }
+
+    /// 按密文字节数更新统计，用于密文没有完整读入内存的解密
+    fn track_len<E>(&self, result: Result<Vec<u8>, E>, len: usize) -> Result<Vec<u8>, E> {
+        let stats = &self.stats;
+        if result.is_ok() {
+            stats.decrypt_count.set(stats.decrypt_count.get().wrapping_add(1));
+            stats.bytes_processed.set(stats.bytes_processed.get() + len as u64);
+            stats.last_blocks.set(len.div_ceil(16) as u32);
+        } else {
+            stats.failure_count.set(stats.failure_count.get().wrapping_add(1));
+            stats.last_blocks.set(0);
+        }
+
+        result
+    }
+
+    /// AES-256-CTR解密核心实现
+    fn decrypt_ctr(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        counter: CtrCounter,
+    ) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if iv_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        if encrypted_data.length() == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        self.check_input_size(encrypted_data)?;
+        Self::check_key_iv_order(key_base64, iv_base64)?;
+
+        let key = Self::decode_key(key_base64)?;
+        let iv = self.decode_iv_with_policy(iv_base64)?;
+
+        let mut buffer = encrypted_data.to_vec();
+        ctr_mode::apply_keystream(counter, &key, &iv, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// AES-256-GCM解密核心实现
+    fn decrypt_gcm(&self, data: &Uint8Array, key_base64: &str) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if data.length() == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        self.check_input_size(data)?;
+
+        let key = Self::decode_key(key_base64)?;
+        gcm_mode::decrypt_combined(&key, &data.to_vec())
+    }
+
+    /// 解析JSON信封并按alg字段选择解密算法
+    fn open_envelope(&self, envelope_json: &str, key_base64: &str) -> Result<Vec<u8>, Error> {
+        let envelope = js_sys::JSON::parse(envelope_json).map_err(|_| Error::EnvelopeJson)?;
+        let field = |name: &'static str| {
+            js_sys::Reflect::get(&envelope, &name.into())
+                .ok()
+                .and_then(|value| value.as_string())
+                .ok_or(Error::MissingEnvelopeField(name))
+        };
+
+        let alg = field("alg")?;
+        let iv_base64 = field("iv")?;
+        let data_base64 = field("data")?;
+        self.check_base64_len(data_base64.len())?;
+        let data = general_purpose::STANDARD
+            .decode(data_base64)
+            .map_err(|e| Error::EnvelopeDataBase64(e.to_string()))?;
+        let data = Uint8Array::from(&data[..]);
+
+        match alg.to_ascii_uppercase().as_str() {
+            "AES-256-CBC" => self.decrypt_with_iv_mode(&data, key_base64, &iv_base64, IvMode::Explicit),
+            "AES-256-GCM" => self.track(self.decrypt_gcm_detached(&data, key_base64, &iv_base64), &data),
+            "AES-256-CTR" => self.track(self.decrypt_ctr(&data, key_base64, &iv_base64, CtrCounter::Be128), &data),
+            _ => Err(Error::UnsupportedCipher(alg)),
+        }
+    }
+
+    /// 分离nonce的AES-256-GCM解密核心实现
+    fn decrypt_gcm_detached(&self, data: &Uint8Array, key_base64: &str, nonce_base64: &str) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if nonce_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        if data.length() == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        self.check_input_size(data)?;
+
+        let key = Self::decode_key(key_base64)?;
+        let nonce = general_purpose::STANDARD
+            .decode(nonce_base64)
+            .map_err(|e| Error::IvBase64(e.to_string()))?;
+
+        gcm_mode::decrypt_detached(&key, &nonce, &data.to_vec())
+    }
+
+    /// AES_256_CBC_HMAC_SHA256记录解密核心实现
+    fn open_cbc_hmac(
+        &self,
+        record: &Uint8Array,
+        enc_key_base64: &str,
+        mac_key_base64: &str,
+        aad: &Uint8Array,
+    ) -> Result<Vec<u8>, Error> {
+        if enc_key_base64.is_empty() || mac_key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if record.length() == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        self.check_input_size(record)?;
+
+        let enc_key = Self::decode_key(enc_key_base64)?;
+        let mac_key = Self::decode_key(mac_key_base64)?;
+        cbc_hmac::open(&enc_key, &mac_key, &record.to_vec(), &aad.to_vec())
+    }
+
+    /// 检查输入是否超过最大限制，应在复制到工作缓冲区之前调用
+    fn check_input_size(&self, encrypted_data: &Uint8Array) -> Result<(), Error> {
+        self.check_input_len(encrypted_data.length() as usize)
+    }
+
+    /// 按Base64文本长度估算解码后的字节数并检查最大限制，在解码之前拒绝过大的输入
+    fn check_base64_len(&self, encoded_len: usize) -> Result<(), Error> {
+        self.check_input_len(encoded_len / 4 * 3)
+    }
+
+    /// 按字节数检查输入是否超过最大限制，用于尚未读入内存的数据
+    fn check_input_len(&self, len: usize) -> Result<(), Error> {
+        if let Some(max_input_size) = self.max_input_size {
+            if len > max_input_size {
+                return Err(Error::InputTooLarge { len, max: max_input_size });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// AES-256-CBC解密核心实现
+    fn decrypt_cbc(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        iv_mode: IvMode,
+    ) -> Result<Vec<u8>, Error> {
+        let buffer = self.decrypt_cbc_blocks(encrypted_data, key_base64, iv_base64, iv_mode)?;
+        self.strip_padding(buffer, encrypted_data)
+    }
+
+    /// 使用原始字节密钥和IV执行AES-256-CBC解密
+    fn decrypt_cbc_raw(&self, encrypted_data: &Uint8Array, key: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+        if key.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if key.len() != 32 {
+            return Err(Error::KeyLength(key.len()));
+        }
+
+        if iv.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        if iv.len() != 16 {
+            return Err(Error::IvLength(iv.len()));
+        }
+
+        // IV已在上面校验，这里只检查密文长度和大小限制
+        self.validate_cbc_data(encrypted_data.length() as usize, "", IvMode::Zero)?;
+
+        let cipher = Aes256CbcDec::new_from_slices(key, iv).map_err(|e| Error::CipherInit(e.to_string()))?;
+        let buffer = Self::decrypt_blocks(cipher, encrypted_data)?;
+        self.strip_padding(buffer, encrypted_data)
+    }
+
+    /// 使用预先扩展的密钥执行AES-256-CBC解密
+    fn decrypt_cbc_prepared(
+        &self,
+        prepared: &PreparedKey,
+        iv_base64: &str,
+        encrypted_data: &Uint8Array,
+        iv_mode: IvMode,
+    ) -> Result<Vec<u8>, Error> {
+        self.validate_cbc_data(encrypted_data.length() as usize, iv_base64, iv_mode)?;
+
+        let (iv, ciphertext) = self.resolve_iv(encrypted_data, iv_base64, iv_mode)?;
+        let cipher = Aes256CbcDec::inner_iv_slice_init(prepared.cipher(), &iv)
+            .map_err(|e| Error::CipherInit(e.to_string()))?;
+
+        let buffer = Self::decrypt_blocks(cipher, &ciphertext)?;
+        self.strip_padding(buffer, encrypted_data)
+    }
+
+    /// 解密批量中的一项并生成结果对象，失败时调用`on_error`
+    fn batch_entry(
+        &self,
+        prepared: &PreparedKey,
+        index: usize,
+        item: &JsValue,
+        on_error: Option<&js_sys::Function>,
+    ) -> js_sys::Object {
+        let entry = js_sys::Object::new();
+        match self.decrypt_batch_item(prepared, index, item) {
+            Ok(decrypted) => {
+                js_sys::Reflect::set(&entry, &"ok".into(), &true.into()).unwrap();
+                js_sys::Reflect::set(&entry, &"data".into(), &Uint8Array::from(&decrypted[..])).unwrap();
+            }
+            Err(e) => {
+                let error = e.to_js(self.locale);
+                js_sys::Reflect::set(&entry, &"ok".into(), &false.into()).unwrap();
+                js_sys::Reflect::set(&entry, &"error".into(), &error).unwrap();
+                if let Some(callback) = on_error {
+                    // 上报回调的异常不影响其余项
+                    let _ = callback.call2(&JsValue::NULL, &(index as u32).into(), &error);
+                }
+            }
+        }
+
+        entry
+    }
+
+    /// 解密批量中的一项：对象项使用其中的iv_base64，Uint8Array项以前16字节为IV
+    fn decrypt_batch_item(&self, prepared: &PreparedKey, index: usize, item: &JsValue) -> Result<Vec<u8>, Error> {
+        let (data, iv_base64, iv_mode) = if let Some(data) = item.dyn_ref::<Uint8Array>() {
+            (data.clone(), String::new(), IvMode::FirstBlock)
+        } else {
+            let field = |name: &str| js_sys::Reflect::get(item, &name.into()).ok();
+            let data = field("data").and_then(|data| data.dyn_into::<Uint8Array>().ok());
+            let iv_base64 = field("iv_base64").and_then(|iv| iv.as_string());
+            match (data, iv_base64) {
+                (Some(data), Some(iv_base64)) => (data, iv_base64, IvMode::Explicit),
+                _ => return Err(Error::InvalidBatchItem(index)),
+            }
+        };
+
+        let result = self.decrypt_cbc_prepared(prepared, &iv_base64, &data, iv_mode);
+        self.track(result, &data)
+    }
+
+    /// 以前一个密文块为IV解密按块对齐的一段密文
+    fn decrypt_range(
+        &self,
+        ciphertext: &Uint8Array,
+        key_base64: &str,
+        prev_block: &Uint8Array,
+        is_final: bool,
+    ) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if ciphertext.length() == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        self.check_input_size(ciphertext)?;
+
+        if !ciphertext.length().is_multiple_of(16) {
+            return Err(Error::UnalignedData);
+        }
+
+        if prev_block.length() != 16 {
+            return Err(Error::PrevBlockLength(prev_block.length() as usize));
+        }
+
+        let key = Self::decode_key(key_base64)?;
+        let cipher = Aes256CbcDec::new_from_slices(&key, &prev_block.to_vec())
+            .map_err(|e| Error::CipherInit(e.to_string()))?;
+
+        let buffer = Self::decrypt_blocks(cipher, ciphertext)?;
+        if is_final {
+            self.strip_padding(buffer, ciphertext)
+        } else {
+            Ok(buffer)
+        }
+    }
+
+    /// 校验并移除PKCS7填充，按设置去掉末尾换行符
+    fn strip_padding(&self, buffer: Vec<u8>, encrypted_data: &Uint8Array) -> Result<Vec<u8>, Error> {
+        self.unpad(buffer, || {
+            encrypted_data.subarray(encrypted_data.length().saturating_sub(16), encrypted_data.length()).to_vec()
+        })
+    }
+
+    /// strip_padding的实现，`last_block`只在填充无效时调用，取出最后一个密文块用于诊断
+    fn unpad(&self, mut buffer: Vec<u8>, last_block: impl FnOnce() -> Vec<u8>) -> Result<Vec<u8>, Error> {
+        // 库自带的UnpadError不区分失败原因，这里逐字节校验以给出准确的诊断信息
+        let buffer_len = buffer.len();
+        let decrypted_len = padding::pkcs7_unpadded_len_for(&buffer, self.pad_block_size).map_err(|fault| Error::Padding {
+            fault,
+            len: buffer_len,
+            last_block: last_block(),
+        })?;
+
+        // 移除填充后原地截断，避免额外复制
+        // 只含一个完整填充块的密文是合法的，解密结果为空
+        buffer.truncate(decrypted_len);
+
+        if self.trim_trailing_newline {
+            buffer.truncate(decrypted_len - padding::trailing_newline_len(&buffer));
+        }
+
+        Ok(buffer)
+    }
+
+    /// 校验输入并解密所有密文块，返回未移除填充的明文
+    fn decrypt_cbc_blocks(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        iv_mode: IvMode,
+    ) -> Result<Vec<u8>, Error> {
+        self.validate_cbc_input(encrypted_data, key_base64, iv_base64, iv_mode)?;
+
+        // 创建解密器
+        let (cipher, ciphertext) = self.prepare_cipher(encrypted_data, key_base64, iv_base64, iv_mode)?;
+
+        Self::decrypt_blocks(cipher, &ciphertext)
+    }
+
+    /// 解密所有密文块，不处理填充
+    fn decrypt_blocks(cipher: Aes256CbcDec, ciphertext: &Uint8Array) -> Result<Vec<u8>, Error> {
+        // 直接从Uint8Array创建buffer，避免额外复制
+        let mut buffer = ciphertext.to_vec();
+        
+        // 验证buffer不为空
+        if buffer.is_empty() {
+            return Err(Error::EmptyBuffer);
+        }
+        
+        // 执行解密，填充由调用方校验
+        cipher
+            .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer)
+            .map_err(|_e| Error::UnalignedData)?;
+
+        Ok(buffer)
+    }
+
+    /// CBC解密的统一输入验证
+    fn validate_cbc_input(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        iv_mode: IvMode,
+    ) -> Result<(), Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if iv_mode == IvMode::Explicit {
+            Self::check_key_iv_order(key_base64, iv_base64)?;
+        }
+
+        self.validate_cbc_data(encrypted_data.length() as usize, iv_base64, iv_mode)
+    }
+
+    /// 检查密钥和IV是否传反：密钥解码为16字节且IV解码为32字节时，
+    /// 单独报告两个长度错误会让人困惑，这里直接提示参数顺序
+    fn check_key_iv_order(key_base64: &str, iv_base64: &str) -> Result<(), Error> {
+        let decoded_len = |value: &str| general_purpose::STANDARD.decode(value).map(|bytes| bytes.len()).ok();
+        if decoded_len(key_base64) == Some(16) && decoded_len(iv_base64) == Some(32) {
+            return Err(Error::KeyIvSwapped);
+        }
+
+        Ok(())
+    }
+
+    /// 校验IV和密文，不涉及密钥
+    fn validate_cbc_data(&self, len: usize, iv_base64: &str, iv_mode: IvMode) -> Result<(), Error> {
+        // Zero和FirstBlock模式不使用IV参数
+        if iv_mode == IvMode::Explicit && iv_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+        
+        if len == 0 {
+            return Err(Error::EmptyData);
+        }
+
+        // 在复制到工作缓冲区之前检查输入大小
+        self.check_input_len(len)?;
+        
+        // 验证加密数据长度（必须是16字节的倍数）
+        if !len.is_multiple_of(16) {
+            return Err(Error::UnalignedData);
+        }
+
+        Ok(())
+    }
+
+    /// 按当前IV模式创建增量解密器，返回解密器和实际需要解密的密文
+    fn incremental_decryptor(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<(IncrementalDecryptor, Uint8Array), Error> {
+        self.validate_cbc_input(encrypted_data, key_base64, iv_base64, self.iv_mode)?;
+
+        let key = Self::decode_key(key_base64)?;
+        let (iv, ciphertext) = self.resolve_iv(encrypted_data, iv_base64, self.iv_mode)?;
+
+        Ok((self.new_incremental(key, iv), ciphertext))
+    }
+
+    /// 用已解码的密钥和IV创建增量解密器，沿用当前实例的填充和去换行设置
+    fn new_incremental(&self, key: Vec<u8>, iv: Vec<u8>) -> IncrementalDecryptor {
+        IncrementalDecryptor::from_parts(key, iv)
+            .with_pad_block_size(self.pad_block_size)
+            .with_trim_trailing_newline(self.trim_trailing_newline)
+    }
+
+    /// 按STREAM_CHUNK_SIZE分段解密，每段明文产生后交给`emit`处理，最后一段为移除填充后的尾部
+    /// 返回拼接后的完整明文
+    fn decrypt_in_chunks(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        mut emit: impl FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<Vec<u8>, Error> {
+        let (mut decryptor, ciphertext) = self.incremental_decryptor(encrypted_data, key_base64, iv_base64)?;
+        let mut plaintext = Vec::with_capacity(ciphertext.length() as usize);
+
+        let mut offset = 0u32;
+        while offset < ciphertext.length() {
+            let end = ciphertext.length().min(offset.saturating_add(STREAM_CHUNK_SIZE));
+            let part = decryptor.feed(&ciphertext.subarray(offset, end).to_vec())?;
+            if !part.is_empty() {
+                emit(&part)?;
+            }
+            plaintext.extend_from_slice(&part);
+            offset = end;
+        }
+
+        let tail = decryptor.finish()?;
+        emit(&tail)?;
+        plaintext.extend_from_slice(&tail);
+
+        Ok(plaintext)
+    }
+
+    /// 按STREAM_CHUNK_SIZE分段解密，每帧耗时达到预算后让出主线程直到下一帧
+    async fn decrypt_budgeted(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        frame_budget_ms: f64,
+        progress_callback: Option<&js_sys::Function>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let to_js = |e: Error| e.to_js(self.locale);
+
+        // NaN、无穷大或非正数的预算会让每帧只解密一段或永不让出，直接拒绝
+        if !frame_budget_ms.is_finite() || frame_budget_ms <= 0.0 {
+            return Err(to_js(Error::InvalidFrameBudget(frame_budget_ms)));
+        }
+
+        let (mut decryptor, ciphertext) = self
+            .incremental_decryptor(encrypted_data, key_base64, iv_base64)
+            .map_err(to_js)?;
+        let total = ciphertext.length();
+        let mut plaintext = Vec::with_capacity(total as usize);
+
+        let mut offset = 0u32;
+        while offset < total {
+            let frame_start = now_ms();
+            loop {
+                let end = total.min(offset.saturating_add(STREAM_CHUNK_SIZE));
+                plaintext.extend_from_slice(&decryptor.feed(&ciphertext.subarray(offset, end).to_vec()).map_err(to_js)?);
+                offset = end;
+
+                if offset >= total || now_ms() - frame_start >= frame_budget_ms {
+                    break;
+                }
+            }
+
+            if let Some(callback) = progress_callback {
+                callback.call2(&JsValue::NULL, &offset.into(), &total.into())?;
+            }
+            if offset < total {
+                next_frame().await?;
+            }
+        }
+        plaintext.extend_from_slice(&decryptor.finish().map_err(to_js)?);
+
+        Ok(plaintext)
+    }
+
+    /// 按STREAM_CHUNK_SIZE分段解密，每次等到空闲回调后再继续
+    async fn decrypt_idle(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> Result<Vec<u8>, JsValue> {
+        let to_js = |e: Error| e.to_js(self.locale);
+
+        let (mut decryptor, ciphertext) = self
+            .incremental_decryptor(encrypted_data, key_base64, iv_base64)
+            .map_err(to_js)?;
+        let total = ciphertext.length();
+        let mut plaintext = Vec::with_capacity(total as usize);
+
+        let mut offset = 0u32;
+        while offset < total {
+            let deadline = next_idle().await?;
+            loop {
+                let end = total.min(offset.saturating_add(STREAM_CHUNK_SIZE));
+                plaintext.extend_from_slice(&decryptor.feed(&ciphertext.subarray(offset, end).to_vec()).map_err(to_js)?);
+                offset = end;
+
+                // 退回setTimeout时没有空闲期限，每次只解密一段
+                if offset >= total || !deadline.as_ref().is_some_and(|deadline| deadline.time_remaining() > 0.0) {
+                    break;
+                }
+            }
+        }
+        plaintext.extend_from_slice(&decryptor.finish().map_err(to_js)?);
+
+        Ok(plaintext)
+    }
+
+    /// 按STREAM_CHUNK_SIZE分段读取Blob并增量解密，读取失败的分段交给回调决定是否重试
+    async fn decrypt_blob_in_chunks(
+        &self,
+        blob: &web_sys::Blob,
+        len: usize,
+        key_base64: &str,
+        iv_base64: &str,
+        on_recoverable_error: Option<&js_sys::Function>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let to_js = |e: Error| e.to_js(self.locale);
+
+        if key_base64.is_empty() {
+            return Err(to_js(Error::EmptyKey));
+        }
+        self.validate_cbc_data(len, iv_base64, self.iv_mode).map_err(to_js)?;
+
+        let key = Self::decode_key(key_base64).map_err(to_js)?;
+        let (iv, mut offset) = match self.iv_mode {
+            IvMode::Explicit => (self.decode_iv_with_policy(iv_base64).map_err(to_js)?, 0),
+            IvMode::Zero => (vec![0u8; 16], 0),
+            IvMode::FirstBlock => {
+                if len < 32 {
+                    return Err(to_js(Error::MissingIvBlock(len)));
+                }
+                (read_blob_range_retrying(blob, 0, 16, on_recoverable_error).await?, 16)
+            }
+        };
+
+        let mut decryptor = self.new_incremental(key, iv);
+        let mut plaintext = Vec::with_capacity(len - offset);
+        while offset < len {
+            let end = len.min(offset + STREAM_CHUNK_SIZE as usize);
+            let chunk = read_blob_range_retrying(blob, offset, end, on_recoverable_error).await?;
+            plaintext.extend_from_slice(&decryptor.feed(&chunk).map_err(to_js)?);
+            offset = end;
+        }
+        plaintext.extend_from_slice(&decryptor.finish().map_err(to_js)?);
+
+        Ok(plaintext)
+    }
+
+    /// 解密第一个密文块
+    fn first_block(&self, encrypted_data: &Uint8Array, key_base64: &str, iv_base64: &str) -> Result<Vec<u8>, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        if self.iv_mode == IvMode::Explicit && iv_base64.is_empty() {
+            return Err(Error::EmptyIv);
+        }
+
+        if encrypted_data.length() < 16 {
+            return Err(Error::MissingFirstBlock(encrypted_data.length() as usize));
+        }
+
+        let (cipher, ciphertext) = self.prepare_cipher(encrypted_data, key_base64, iv_base64, self.iv_mode)?;
+        let block = ciphertext.subarray(0, 16);
+        Self::decrypt_blocks(cipher, &block)
+    }
+
+    /// 只解密开头和最后一块，检查文件头和填充
+    /// 返回识别出的图片类型（无法识别时为空字符串）和发现的问题；输入本身无效时直接返回错误
+    fn dry_run_checks(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<(String, Vec<Error>), Error> {
+        self.validate_cbc_input(encrypted_data, key_base64, iv_base64, self.iv_mode)?;
+
+        let key = Self::decode_key(key_base64)?;
+        let (iv, ciphertext) = self.resolve_iv(encrypted_data, iv_base64, self.iv_mode)?;
+        let len = ciphertext.length() as usize;
+        let mut issues = Vec::new();
+
+        // 解密开头几块用于识别文件头
+        let prefix_len = len.min(DRY_RUN_PREFIX_BLOCKS * 16);
+        let mut prefix = ciphertext.subarray(0, prefix_len as u32).to_vec();
+        Aes256CbcDec::new_from_slices(&key, &iv)
+            .map_err(|e| Error::CipherInit(e.to_string()))?
+            .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut prefix)
+            .map_err(|_e| Error::UnalignedData)?;
+
+        // CBC中任一块只依赖前一个密文块，最后一块可以单独解密来检查填充
+        let last_block = if prefix_len == len {
+            prefix[len - 16..].to_vec()
+        } else {
+            let chain = ciphertext.subarray((len - 32) as u32, (len - 16) as u32).to_vec();
+            let mut block = ciphertext.subarray((len - 16) as u32, len as u32).to_vec();
+            Aes256CbcDec::new_from_slices(&key, &chain)
+                .map_err(|e| Error::CipherInit(e.to_string()))?
+                .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut block)
+                .map_err(|_e| Error::UnalignedData)?;
+            block
+        };
+
+        if let Err(fault) = padding::pkcs7_unpadded_len_for(&last_block, self.pad_block_size) {
+            issues.push(Error::Padding {
+                fault,
+                len,
+                last_block: ciphertext.subarray((len - 16) as u32, len as u32).to_vec(),
+            });
+        }
+
+        let detected = self.detect_image_type(&prefix);
+        if detected.is_empty() {
+            issues.push(Error::UnknownImageType);
+        }
+
+        Ok((detected, issues))
+    }
+
+    /// 按IV模式确定IV并创建解密器
+    /// 返回解密器和实际需要解密的密文（FirstBlock模式下不含首块）
+    fn prepare_cipher(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        iv_mode: IvMode,
+    ) -> Result<(Aes256CbcDec, Uint8Array), Error> {
+        let key = Self::decode_key(key_base64)?;
+        let (iv, ciphertext) = self.resolve_iv(encrypted_data, iv_base64, iv_mode)?;
+
+        let cipher = Aes256CbcDec::new_from_slices(&key, &iv)
+            .map_err(|e| Error::CipherInit(e.to_string()))?;
+        Ok((cipher, ciphertext))
+    }
+
+    /// 按IV模式确定IV，返回IV和实际需要解密的密文
+    fn resolve_iv(&self, encrypted_data: &Uint8Array, iv_base64: &str, iv_mode: IvMode) -> Result<(Vec<u8>, Uint8Array), Error> {
+        let (iv, ciphertext) = match iv_mode {
+            IvMode::Explicit => (self.decode_iv_with_policy(iv_base64)?, encrypted_data.clone()),
+            IvMode::Zero => (vec![0u8; 16], encrypted_data.clone()),
+            IvMode::FirstBlock => {
+                // 首块作为IV，之后至少还需要一个密文块
+                if encrypted_data.length() < 32 {
+                    return Err(Error::MissingIvBlock(encrypted_data.length() as usize));
+                }
+                let iv = encrypted_data.subarray(0, 16).to_vec();
+                (iv, encrypted_data.subarray(16, encrypted_data.length()))
+            }
+        };
+
+        Ok((iv, ciphertext))
+    }
+
+    /// 解码Base64密钥并验证长度为32字节
+    pub(crate) fn decode_key(key_base64: &str) -> Result<Vec<u8>, Error> {
+        let key = general_purpose::STANDARD
+            .decode(key_base64)
+            .map_err(|e| Error::KeyBase64(e.to_string()))?;
+
+        if key.len() != 32 {
+            return Err(Error::KeyLength(key.len()));
+        }
+
+        Ok(key)
+    }
+
+    /// 按严格/宽松设置解码IV
+    /// 宽松模式下，严格解码失败时尝试把缺少前导零的IV左侧补零
+    fn decode_iv_with_policy(&self, iv_base64: &str) -> Result<Vec<u8>, Error> {
+        let strict_error = match Self::decode_iv(iv_base64) {
+            Ok(iv) => return Ok(iv),
+            Err(e) if self.strict_iv => return Err(e),
+            Err(e) => e,
+        };
+
+        let padded = general_purpose::STANDARD
+            .decode(iv_base64)
+            .ok()
+            .filter(|iv| iv.len() == 15)
+            .map(|iv| [&[0u8][..], &iv].concat())
+            .or_else(|| format::decode_short_hex_iv(iv_base64));
+
+        let Some(iv) = padded else {
+            return Err(strict_error);
+        };
+
+        console_log!("⚠️ IV缺少前导零，已在左侧补零后使用: {}", iv_base64);
+        Ok(iv)
+    }
+
+    /// 解码Base64 IV并验证长度为16字节
+    pub(crate) fn decode_iv(iv_base64: &str) -> Result<Vec<u8>, Error> {
+        let iv = general_purpose::STANDARD
+            .decode(iv_base64)
+            .map_err(|e| Error::IvBase64(e.to_string()))?;
+
+        if iv.len() != 16 {
+            return Err(Error::IvLength(iv.len()));
         }
 
-        // 直接创建Uint8Array，避免额外的to_vec()调用
-        Ok(Uint8Array::from(decrypted))
+        Ok(iv)
+    }
+
+    /// 解密、解码并将像素写入OffscreenCanvas
+    fn draw_to_offscreen(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+        canvas: &web_sys::OffscreenCanvas,
+    ) -> Result<(), JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+        let image = raster::decode(&decrypted).map_err(|e| e.to_js(self.locale))?;
+        let (width, height) = (image.width(), image.height());
+        let pixels = image.into_rgba8().into_raw();
+
+        // 画布尺寸与图片不一致时调整画布
+        if canvas.width() != width {
+            canvas.set_width(width);
+        }
+        if canvas.height() != height {
+            canvas.set_height(height);
+        }
+
+        let context = canvas
+            .get_context("2d")?
+            .ok_or_else(|| Error::NoCanvasContext.to_js(self.locale))?
+            .dyn_into::<web_sys::OffscreenCanvasRenderingContext2d>()?;
+
+        let image_data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&pixels[..]),
+            width,
+            height,
+        )?;
+        context.put_image_data(&image_data, 0.0, 0.0)?;
+
+        Ok(())
+    }
+
+    /// 解密后以检测到的MIME类型构造Blob，并在当前全局作用域调用createImageBitmap
+    fn create_image_bitmap(
+        &self,
+        encrypted_data: &Uint8Array,
+        key_base64: &str,
+        iv_base64: &str,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let decrypted = self
+            .decrypt_to_vec(encrypted_data, key_base64, iv_base64)
+            .map_err(|e| e.to_js(self.locale))?;
+
+        let options = web_sys::BlobPropertyBag::new();
+        let mime = self.detect_image_type(&decrypted);
+        if !mime.is_empty() {
+            options.set_type(&mime);
+        }
+
+        let parts = js_sys::Array::of1(&Uint8Array::from(&decrypted[..]));
+        let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+
+        // 同时支持主线程和Worker
+        let global = js_sys::global();
+        if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+            window.create_image_bitmap_with_blob(&blob)
+        } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+            worker.create_image_bitmap_with_blob(&blob)
+        } else {
+            Err(Error::NoGlobalScope.to_js(self.locale))
+        }
     }
 
     /// 检测图片文件类型
     fn detect_image_type(&self, data: &[u8]) -> String {
+        // 自定义签名优先，后注册的覆盖先注册的
+        if let Some((_, mime)) = self.custom_magic.iter().rev().find(|(signature, _)| data.starts_with(signature)) {
+            return mime.clone();
+        }
+
         if data.len() < 8 {
             return String::new();
         }
@@ -230,15 +3172,83 @@ impl ImageDecryptor {
             return "image/webp".to_string();
         }
         
+        // QOI
+        if data.starts_with(b"qoif") {
+            return "image/qoi".to_string();
+        }
+
         // BMP
         if data.starts_with(b"BM") {
             return "image/bmp".to_string();
         }
 
+        // TIFF，DNG在IFD0中带有DNGVersion标签；
+        // 基于TIFF的相机RAW格式中，CR2可由文件头识别，NEF和ARW按IFD0的Make标签区分
+        if let Some(tiff) = tiff::parse(data) {
+            if tiff.has_tag(tiff::DNG_VERSION) {
+                return "image/x-adobe-dng".to_string();
+            }
+            if tiff.is_cr2() {
+                return "image/x-canon-cr2".to_string();
+            }
+            match tiff.ascii(tiff::MAKE).as_deref() {
+                Some(make) if make.starts_with("NIKON") => return "image/x-nikon-nef".to_string(),
+                Some(make) if make.starts_with("SONY") => return "image/x-sony-arw".to_string(),
+                _ => {}
+            }
+            return "image/tiff".to_string();
+        }
+
         String::new()
     }
 }
 
+/// 工具函数：在运行时开启或关闭控制台输出
+/// 关闭后模块不再输出任何日志，包括加载提示和各类警告；
+/// 构建时不启用`logging`特性则始终不输出
+/// 
+/// # 参数
+/// - `enabled`: 是否输出日志，默认为true
+#[wasm_bindgen]
+pub fn set_logging_enabled(enabled: bool) {
+    LOGGING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 从文件头解析图片尺寸，支持JPEG、PNG、GIF、WebP和QOI
+fn header_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    format::webp_dimensions(data)
+        .or_else(|| format::qoi_dimensions(data))
+        .or_else(|| png::dimensions(data))
+        .or_else(|| gif::dimensions(data))
+        .or_else(|| jpeg::inspect(data).map(|info| (info.width, info.height)))
+}
+
+/// 检查文件尾是否符合检测到的格式，不支持的格式返回None
+fn footer_valid(file_type: &str, data: &[u8]) -> Option<bool> {
+    match file_type {
+        "image/jpeg" => Some(data.ends_with(&[0xFF, jpeg::EOI])),
+        "image/png" => Some(data.ends_with(png::IEND_CHUNK)),
+        "image/gif" => Some(data.last() == Some(&gif::TRAILER)),
+        // RIFF长度字段为文件总长度减8
+        "image/webp" => {
+            let declared = u32::from_le_bytes(data.get(4..8)?.try_into().ok()?) as usize;
+            Some(declared.checked_add(8) == Some(data.len()))
+        }
+        _ => None,
+    }
+}
+
+/// 从文件头解析像素格式，支持PNG（IHDR）和JPEG（SOF）
+fn header_pixel_format(data: &[u8]) -> Option<format::PixelFormat> {
+    png::pixel_format(data).or_else(|| {
+        jpeg::inspect(data).map(|info| format::PixelFormat {
+            bit_depth: info.precision,
+            channels: info.sampling.len() as u8,
+            color_type: info.color_type(),
+        })
+    })
+}
+
 /// 工具函数：检查WASM SIMD支持
 #[wasm_bindgen]
 pub fn check_simd_support() -> bool {
@@ -247,6 +3257,607 @@ pub fn check_simd_support() -> bool {
     cfg!(target_feature = "simd128")
 }
 
+/// 工具函数：使用NIST SP 800-38A的AES-256-CBC已知答案测试向量自检
+/// 用于在处理用户数据之前确认当前构建中的AES实现正确，可发现编译或链接异常的加密后端；
+/// 同时校验一条AES_256_CBC_HMAC_SHA256记录的解密结果
+/// 
+/// # 返回
+/// 包含passed（是否全部通过）和failures（未通过的向量名称数组）字段的对象
+#[wasm_bindgen]
+pub fn self_test() -> JsValue {
+    let failures = selftest::run();
+
+    let failure_list = js_sys::Array::new();
+    for name in &failures {
+        failure_list.push(&(*name).into());
+    }
+
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"passed".into(), &failures.is_empty().into()).unwrap();
+    js_sys::Reflect::set(&result, &"failures".into(), &failure_list).unwrap();
+
+    result.into()
+}
+
+/// 派生IV时截取的HMAC输出长度
+const DERIVED_IV_SIZE: usize = 16;
+
+/// 工具函数：由文件ID确定性地派生IV
+/// IV = HMAC-SHA256(iv_key, file_id)的前16字节，客户端和服务端使用同一实现即可免去保存每个文件的IV。
+/// 截断HMAC输出用作IV是可以接受的，因为CBC只要求IV不可预测；
+/// 但截断后的值不能用于消息认证
+/// 
+/// # 参数
+/// - `iv_key_base64`: Base64编码的32字节派生密钥，应与加密密钥不同
+/// - `file_id`: 文件路径或ID，按UTF-8字节参与计算
+/// 
+/// # 返回
+/// 16字节IV（Uint8Array），Base64编码后可传给decrypt_image；派生密钥无效时返回错误
+#[wasm_bindgen]
+pub fn derive_iv_hmac(iv_key_base64: &str, file_id: &str) -> Result<Uint8Array, JsValue> {
+    let locale = Locale::default();
+    if iv_key_base64.is_empty() {
+        return Err(Error::EmptyKey.to_js(locale));
+    }
+
+    let iv_key = ImageDecryptor::decode_key(iv_key_base64).map_err(|e| e.to_js(locale))?;
+    let mac = hash::hmac_sha256(&iv_key, file_id.as_bytes());
+
+    Ok(Uint8Array::from(&mac[..DERIVED_IV_SIZE]))
+}
+
+/// 工具函数：获取AES在指定密钥长度下的参数
+/// 
+/// # 参数
+/// - `key_bits`: 密钥位数，128、192或256
+/// 
+/// # 返回
+/// 包含rounds（轮数，10/12/14）、blockSizeBytes（分组大小，恒为16）、keyBytes（密钥字节数）字段的对象，
+/// 密钥位数不受支持时返回null
+#[wasm_bindgen]
+pub fn cipher_info(key_bits: u32) -> JsValue {
+    let rounds: u32 = match key_bits {
+        128 => 10,
+        192 => 12,
+        256 => 14,
+        _ => return JsValue::NULL,
+    };
+
+    let info = js_sys::Object::new();
+    js_sys::Reflect::set(&info, &"rounds".into(), &rounds.into()).unwrap();
+    js_sys::Reflect::set(&info, &"blockSizeBytes".into(), &(padding::BLOCK_SIZE as u32).into()).unwrap();
+    js_sys::Reflect::set(&info, &"keyBytes".into(), &(key_bits / 8).into()).unwrap();
+
+    info.into()
+}
+
+/// 工具函数：根据密文长度估算明文长度
+/// CBC模式下PKCS7填充为1~16字节，因此明文长度在`密文长度-16`到`密文长度-1`之间；
+/// 流式模式下明文与密文等长
+/// 
+/// # 参数
+/// - `ciphertext_len`: 密文字节数
+/// - `mode`: 加密模式
+/// 
+/// # 返回
+/// 包含min和max字段的对象
+#[wasm_bindgen]
+pub fn estimate_plaintext_size(ciphertext_len: usize, mode: CipherMode) -> JsValue {
+    let (min, max) = match mode {
+        CipherMode::Cbc => (ciphertext_len.saturating_sub(16), ciphertext_len.saturating_sub(1)),
+        CipherMode::Ctr => (ciphertext_len, ciphertext_len),
+        CipherMode::Gcm => {
+            let len = ciphertext_len.saturating_sub(gcm_mode::TAG_SIZE);
+            (len, len)
+        }
+    };
+
+    let info = js_sys::Object::new();
+    js_sys::Reflect::set(&info, &"min".into(), &min.into()).unwrap();
+    js_sys::Reflect::set(&info, &"max".into(), &max.into()).unwrap();
+
+    info.into()
+}
+
+/// 工具函数：计算明文加密后的准确密文长度
+/// CBC模式下PKCS7总会追加1~16字节填充，明文恰好为16字节倍数时也会多出一整块
+/// 
+/// # 参数
+/// - `plaintext_len`: 明文字节数
+/// - `mode`: 加密模式
+/// 
+/// # 返回
+/// 密文字节数；GCM包含16字节认证标签，但不含nonce
+#[wasm_bindgen]
+pub fn ciphertext_len_for(plaintext_len: usize, mode: CipherMode) -> usize {
+    match mode {
+        CipherMode::Cbc => plaintext_len + padding::pkcs7_pad_len(plaintext_len),
+        CipherMode::Ctr => plaintext_len,
+        CipherMode::Gcm => plaintext_len + gcm_mode::TAG_SIZE,
+    }
+}
+
+/// 工具函数：计算PKCS7为明文追加的填充字节数
+/// 结果总在1~16之间：明文恰好为16字节倍数时也要追加一整块（16字节），不会是0。
+/// CBC密文长度即明文长度加上该值，与ciphertext_len_for一致
+/// 
+/// # 参数
+/// - `plaintext_len`: 明文字节数
+/// 
+/// # 返回
+/// 填充字节数
+#[wasm_bindgen]
+pub fn pkcs7_pad_bytes(plaintext_len: usize) -> usize {
+    padding::pkcs7_pad_len(plaintext_len)
+}
+
+/// wasm32线性内存的地址空间上限（65536页 × 64KiB），作为内存上限的最大值
+const WASM_ADDRESS_SPACE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// WebAssembly内存页大小
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+/// 单次解密的峰值内存约为密文长度的倍数：
+/// 密文从JS复制到WASM（1x）、解密缓冲区（1x）、结果转换为Uint8Array前的拷贝或图片解码等中间数据（约1x）
+const PEAK_MEMORY_FACTOR: u64 = 3;
+
+/// 读取内存声明的最大页数对应的字节数
+/// 使用JS类型反射的`Memory.prototype.type()`，环境不支持或内存未声明最大值时返回None
+fn declared_memory_maximum(memory: &js_sys::WebAssembly::Memory) -> Option<u64> {
+    let type_fn = js_sys::Reflect::get(memory, &"type".into()).ok()?.dyn_into::<js_sys::Function>().ok()?;
+    let descriptor = type_fn.call0(memory).ok()?;
+    let pages = js_sys::Reflect::get(&descriptor, &"maximum".into()).ok()?.as_f64()?;
+
+    Some((pages as u64).saturating_mul(WASM_PAGE_SIZE))
+}
+
+/// 工具函数：估算当前可安全解密的最大密文长度
+/// 估算基于以下假设：
+/// - 已分配的线性内存视为全部被占用，不计入其中的空闲块，结果偏保守
+/// - 内存上限取运行时读到的声明最大值（如构建时设置了`--max-memory`），
+///   读不到时（浏览器不支持类型反射）退回wasm32的4GB地址空间，两者取较小值；
+///   实际能否增长还取决于浏览器的内存配额，部分浏览器把wasm32内存限制在2GB
+/// - 解密峰值约为密文长度的3倍
+/// 
+/// # 返回
+/// 建议的最大密文字节数（已向下对齐到16字节）
+#[wasm_bindgen]
+pub fn max_safe_input() -> usize {
+    let memory = wasm_bindgen::memory().unchecked_into::<js_sys::WebAssembly::Memory>();
+    let current = memory.buffer().unchecked_into::<js_sys::ArrayBuffer>().byte_length() as u64;
+    let maximum = declared_memory_maximum(&memory).map_or(WASM_ADDRESS_SPACE, |max| max.min(WASM_ADDRESS_SPACE));
+
+    let available = maximum.saturating_sub(current) / PEAK_MEMORY_FACTOR;
+    let aligned = available / 16 * 16;
+
+    usize::try_from(aligned).unwrap_or(usize::MAX / 16 * 16)
+}
+
+/// 小于该大小的输入始终推荐WASM：SubtleCrypto的异步调用开销会抵消其速度优势
+const SUBTLE_MIN_INPUT: usize = 256 * 1024;
+
+/// 微基准测试的数据量
+const BENCHMARK_SIZE: usize = 64 * 1024;
+
+/// WASM解密吞吐量低于该值（MB/s）时推荐SubtleCrypto
+const WASM_MIN_THROUGHPUT: f64 = 40.0;
+
+/// 读取Blob中`start..end`范围的字节
+async fn read_blob_range(blob: &web_sys::Blob, start: usize, end: usize) -> Result<Vec<u8>, JsValue> {
+    let slice = blob.slice_with_f64_and_f64(start as f64, end as f64)?;
+    let buffer = wasm_bindgen_futures::JsFuture::from(slice.array_buffer()).await?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+/// 读取Blob的指定字节范围，失败时交给回调决定是否重试
+/// 回调收到`{ offset, end, attempt, error }`，返回真值时重新读取；
+/// 没有回调、回调返回假值或抛出异常时返回错误
+async fn read_blob_range_retrying(
+    blob: &web_sys::Blob,
+    start: usize,
+    end: usize,
+    on_recoverable_error: Option<&js_sys::Function>,
+) -> Result<Vec<u8>, JsValue> {
+    let mut attempt = 1u32;
+    loop {
+        let error = match read_blob_range(blob, start, end).await {
+            Ok(chunk) => return Ok(chunk),
+            Err(error) => error,
+        };
+
+        let Some(callback) = on_recoverable_error else { return Err(error) };
+
+        let details = js_sys::Object::new();
+        js_sys::Reflect::set(&details, &"offset".into(), &(start as f64).into()).unwrap();
+        js_sys::Reflect::set(&details, &"end".into(), &(end as f64).into()).unwrap();
+        js_sys::Reflect::set(&details, &"attempt".into(), &attempt.into()).unwrap();
+        js_sys::Reflect::set(&details, &"error".into(), &error).unwrap();
+
+        if !callback.call1(&JsValue::NULL, &details)?.is_truthy() {
+            return Err(error);
+        }
+        attempt += 1;
+    }
+}
+
+/// 等待下一帧：窗口中使用requestAnimationFrame，Worker中使用setTimeout(0)，
+/// 两者都不可用时立即继续
+async fn next_frame() -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let scheduled = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+            window.request_animation_frame(&resolve).is_ok()
+        } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+            worker.set_timeout_with_callback(&resolve).is_ok()
+        } else {
+            false
+        };
+
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+
+    wasm_bindgen_futures::JsFuture::from(promise).await.map(|_| ())
+}
+
+/// 等待浏览器空闲：窗口中使用requestIdleCallback并返回空闲期限，
+/// 不支持时（包括Worker中）退回setTimeout(0)并返回None，两者都不可用时立即继续
+async fn next_idle() -> Result<Option<web_sys::IdleDeadline>, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let scheduled = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+            window.request_idle_callback(&resolve).is_ok() || window.set_timeout_with_callback(&resolve).is_ok()
+        } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+            worker.set_timeout_with_callback(&resolve).is_ok()
+        } else {
+            false
+        };
+
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+
+    // setTimeout和立即继续时回调参数为undefined或null
+    let deadline = wasm_bindgen_futures::JsFuture::from(promise).await?;
+    Ok((!deadline.is_undefined() && !deadline.is_null()).then(|| deadline.unchecked_into()))
+}
+
+/// 当前时间（毫秒），优先使用高精度的performance.now()
+fn now_ms() -> f64 {
+    let global = js_sys::global();
+    let performance = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.performance()
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.performance()
+    } else {
+        None
+    };
+
+    performance.map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// 工具函数：根据输入大小、SIMD支持和微基准测试结果推荐解密后端
+/// 本模块不会调用SubtleCrypto，只给出建议，由调用方决定如何路由。判断顺序：
+/// 1. 输入小于256KB时推荐"wasm"
+/// 2. 支持SIMD时推荐"wasm"
+/// 3. 否则解密64KB数据测速，吞吐量低于40MB/s时推荐"subtle"
+/// 
+/// # 参数
+/// - `input_size`: 待解密的数据大小（字节）
+/// 
+/// # 返回
+/// "wasm"或"subtle"
+#[wasm_bindgen]
+pub fn recommend_backend(input_size: usize) -> String {
+    if input_size < SUBTLE_MIN_INPUT || check_simd_support() {
+        return "wasm".to_string();
+    }
+
+    let mut buffer = vec![0u8; BENCHMARK_SIZE];
+    let cipher = Aes256CbcDec::new_from_slices(&[0u8; 32], &[0u8; 16]).expect("固定长度的密钥和IV");
+
+    let start = now_ms();
+    let _ = cipher.decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer);
+    let elapsed = (now_ms() - start).max(0.001);
+
+    let throughput = BENCHMARK_SIZE as f64 / 1_048_576.0 / (elapsed / 1000.0);
+    if throughput < WASM_MIN_THROUGHPUT {
+        "subtle".to_string()
+    } else {
+        "wasm".to_string()
+    }
+}
+
+/// 工具函数：分阶段测量解密耗时
+/// 分别计时Base64解码（密钥、IV和密文）、解密器初始化（含密钥扩展）和分组解密三个阶段，
+/// 用于判断小图片的开销主要来自Base64解码还是AES本身。测试数据为全零，不校验填充
+/// 
+/// # 参数
+/// - `size_bytes`: 测试密文大小（字节），向上对齐到16字节
+/// - `iterations`: 重复次数，为0时按1次计算
+/// 
+/// # 返回
+/// 包含base64DecodeMs、cipherInitMs、decryptMs字段（各阶段的平均毫秒数）的对象
+#[wasm_bindgen]
+pub fn benchmark_stages(size_bytes: usize, iterations: u32) -> JsValue {
+    let iterations = iterations.max(1);
+    let size = size_bytes.max(1).div_ceil(16) * 16;
+
+    let key_base64 = general_purpose::STANDARD.encode([0u8; 32]);
+    let iv_base64 = general_purpose::STANDARD.encode([0u8; 16]);
+    let data_base64 = general_purpose::STANDARD.encode(vec![0u8; size]);
+
+    let (mut decode_ms, mut init_ms, mut decrypt_ms) = (0.0, 0.0, 0.0);
+    for _ in 0..iterations {
+        let start = now_ms();
+        let key = general_purpose::STANDARD.decode(&key_base64).unwrap_or_default();
+        let iv = general_purpose::STANDARD.decode(&iv_base64).unwrap_or_default();
+        let mut buffer = general_purpose::STANDARD.decode(&data_base64).unwrap_or_default();
+        let decoded = now_ms();
+
+        let cipher = Aes256CbcDec::new_from_slices(&key, &iv).expect("固定长度的密钥和IV");
+        let initialized = now_ms();
+
+        let _ = cipher.decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer);
+        let finished = now_ms();
+
+        decode_ms += decoded - start;
+        init_ms += initialized - decoded;
+        decrypt_ms += finished - initialized;
+    }
+
+    let count = f64::from(iterations);
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"base64DecodeMs".into(), &(decode_ms / count).into()).unwrap();
+    js_sys::Reflect::set(&result, &"cipherInitMs".into(), &(init_ms / count).into()).unwrap();
+    js_sys::Reflect::set(&result, &"decryptMs".into(), &(decrypt_ms / count).into()).unwrap();
+
+    result.into()
+}
+
+/// 工具函数：按布局描述拆分拼接在一起的数据
+/// 布局由逗号分隔的`名称:长度`组成，最后一个字段可用`*`表示剩余全部字节，
+/// 例如"iv:16,ct:*"或"key:32,iv:16,ct:*"
+/// 
+/// # 参数
+/// - `data`: 拼接后的数据
+/// - `layout`: 布局描述
+/// 
+/// # 返回
+/// 以字段名为键、Uint8Array为值的对象；布局无效或超出数据长度时返回错误
+#[wasm_bindgen]
+pub fn parse_blob(data: &Uint8Array, layout: &str) -> Result<JsValue, JsValue> {
+    let locale = Locale::default();
+    let fields = format::parse_layout(layout).map_err(|e| e.to_js(locale))?;
+
+    let total = data.length() as usize;
+    let result = js_sys::Object::new();
+    let mut offset = 0usize;
+
+    for field in fields {
+        let len = field.len.unwrap_or(total.saturating_sub(offset));
+        let end = offset
+            .checked_add(len)
+            .filter(|&end| end <= total)
+            .ok_or_else(|| Error::LayoutExceedsData { needed: offset.saturating_add(len), len: total }.to_js(locale))?;
+
+        let slice = data.slice(offset as u32, end as u32);
+        js_sys::Reflect::set(&result, &JsValue::from_str(&field.name), &slice).unwrap();
+        offset = end;
+    }
+
+    Ok(result.into())
+}
+
+/// 工具函数：解析JPEG的色度抽样、ICC配置文件和编码方式
+/// 只遍历SOS之前的文件头标记段，开销很小
+/// 
+/// # 参数
+/// - `data`: 解密后的JPEG数据
+/// 
+/// # 返回
+/// 包含width、height、subsampling（如"4:2:0"）、hasIccProfile、progressive字段的对象，
+/// 不是JPEG或找不到SOF时返回null
+#[wasm_bindgen]
+pub fn inspect_jpeg(data: &Uint8Array) -> JsValue {
+    let Some(info) = jpeg::inspect(&data.to_vec()) else {
+        return JsValue::NULL;
+    };
+
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"width".into(), &info.width.into()).unwrap();
+    js_sys::Reflect::set(&result, &"height".into(), &info.height.into()).unwrap();
+    js_sys::Reflect::set(&result, &"subsampling".into(), &info.subsampling().into()).unwrap();
+    js_sys::Reflect::set(&result, &"hasIccProfile".into(), &info.has_icc_profile.into()).unwrap();
+    js_sys::Reflect::set(&result, &"progressive".into(), &info.progressive.into()).unwrap();
+
+    result.into()
+}
+
+/// 工具函数：检查JPEG在首个SOS之前的标记结构
+/// 比文件头检测更严格、比完整解码开销更小：从SOI开始逐段遍历，
+/// 要求出现SOF后才出现SOS，且DRI（重启间隔）段长度正确，可发现文件头正确但标记段损坏的数据
+/// 
+/// # 参数
+/// - `data`: 解密后的JPEG数据
+/// 
+/// # 返回
+/// 包含valid、markersSeen（依次遇到的标记值数组，如0xD8、0xE0）和
+/// truncatedAt（标记段截断或损坏的偏移，没有时为null）字段的对象；不是JPEG时valid为false且markersSeen为空
+#[wasm_bindgen]
+pub fn validate_jpeg_structure(data: &Uint8Array) -> JsValue {
+    let structure = jpeg::validate_structure(&data.to_vec());
+
+    let markers = js_sys::Array::new();
+    let mut valid = false;
+    let mut truncated_at = JsValue::NULL;
+    if let Some(structure) = structure {
+        for marker in &structure.markers {
+            markers.push(&(*marker).into());
+        }
+        valid = structure.valid;
+        if let Some(offset) = structure.truncated_at {
+            truncated_at = (offset as u32).into();
+        }
+    }
+
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"valid".into(), &valid.into()).unwrap();
+    js_sys::Reflect::set(&result, &"markersSeen".into(), &markers).unwrap();
+    js_sys::Reflect::set(&result, &"truncatedAt".into(), &truncated_at).unwrap();
+
+    result.into()
+}
+
+/// 工具函数：检测解密后的图片是否为动画
+/// 支持GIF（多个图像描述符）、WebP（VP8X动画标志或ANMF块）和APNG（acTL块）。
+/// 数据损坏时按已解析到的内容判断，无法确定时视为非动画
+/// 
+/// # 参数
+/// - `data`: 解密后的图片数据
+/// 
+/// # 返回
+/// 是否为动画
+#[wasm_bindgen]
+pub fn is_animated(data: &Uint8Array) -> bool {
+    let data = data.to_vec();
+
+    gif::frame_count(&data, 2).is_some_and(|frames| frames > 1)
+        || format::webp_is_animated(&data)
+        || png::is_animated(&data)
+}
+
+/// 工具函数：判断解密后的数据是文本还是二进制
+/// 用于区分SVG、JSON等文本资源和位图，这类文本格式没有可靠的魔数。
+/// 只扫描前4KB：要求是合法的UTF-8，且不含换行、制表符等以外的控制字符
+/// 
+/// # 参数
+/// - `data`: 解密后的数据
+/// 
+/// # 返回
+/// "text"或"binary"
+#[wasm_bindgen]
+pub fn classify_content(data: &Uint8Array) -> String {
+    let prefix = data.subarray(0, data.length().min(format::TEXT_SCAN_SIZE as u32)).to_vec();
+
+    if format::looks_like_text(&prefix) { "text" } else { "binary" }.to_string()
+}
+
+/// 工具函数：提取图片内嵌的ICC配置文件
+/// 支持JPEG（APP2中的ICC_PROFILE段，多段配置文件会按序号拼接）和PNG（iCCP块，自动解压）
+/// 
+/// # 参数
+/// - `data`: 解密后的图片数据
+/// 
+/// # 返回
+/// ICC配置文件原始字节（Uint8Array），没有配置文件、格式不支持或数据损坏时返回null
+#[wasm_bindgen]
+pub fn extract_icc_profile(data: &Uint8Array) -> JsValue {
+    let data = data.to_vec();
+    let profile = jpeg::icc_profile(&data).or_else(|| png::icc_profile(&data));
+
+    match profile {
+        Some(profile) => Uint8Array::from(&profile[..]).into(),
+        None => JsValue::NULL,
+    }
+}
+
+/// decrypt_at_ptr的错误码：有指针为空
+const PTR_ERR_NULL: i32 = -1;
+/// decrypt_at_ptr的错误码：密文长度为0
+const PTR_ERR_EMPTY: i32 = -2;
+/// decrypt_at_ptr的错误码：密文长度不是16字节的倍数
+const PTR_ERR_UNALIGNED: i32 = -3;
+/// decrypt_at_ptr的错误码：密文长度超过i32可表示的范围
+const PTR_ERR_TOO_LARGE: i32 = -4;
+/// decrypt_at_ptr的错误码：PKCS7填充无效（通常是密钥或IV错误）
+const PTR_ERR_PADDING: i32 = -5;
+/// decrypt_at_ptr的错误码：无法用给定的密钥和IV初始化解密器
+const PTR_ERR_KEY: i32 = -6;
+
+/// 低层接口：直接在WASM线性内存中按地址解密AES-256-CBC数据
+/// 供共享同一块WebAssembly.Memory、自行管理内存的嵌入方使用，完全绕过Uint8Array。
+/// 密文先复制到输出区域再原地解密，因此输出区域可以与密文区域相同（原地解密）或任意重叠
+/// 
+/// # 参数
+/// - `encrypted_ptr`: 密文起始地址
+/// - `len`: 密文字节数，必须是16的倍数
+/// - `key_ptr`: 32字节原始密钥的起始地址
+/// - `iv_ptr`: 16字节原始IV的起始地址
+/// - `out_ptr`: 输出区域起始地址，容量至少为`len`字节
+/// 
+/// # 返回
+/// 成功时返回移除填充后的明文长度（明文位于`out_ptr`开头）；失败时返回负数错误码：
+/// -1 指针为空，-2 密文为空，-3 长度未按16字节对齐，-4 长度过大，-5 填充无效，-6 解密器初始化失败。
+/// 失败时输出区域的内容不确定，不应使用
+/// 
+/// # Safety
+/// 调用方必须保证：
+/// - `encrypted_ptr`指向`len`字节、`key_ptr`指向32字节、`iv_ptr`指向16字节的已初始化可读内存；
+/// - `out_ptr`指向至少`len`字节的可写内存，且调用期间不会被其他代码访问；
+/// - 密钥和IV区域与输出区域不重叠；
+/// - 所有地址都位于本模块的线性内存中，并且由调用方负责分配和释放，本函数不会持有任何指针
+#[wasm_bindgen]
+pub unsafe fn decrypt_at_ptr(
+    encrypted_ptr: *const u8,
+    len: usize,
+    key_ptr: *const u8,
+    iv_ptr: *const u8,
+    out_ptr: *mut u8,
+) -> i32 {
+    if encrypted_ptr.is_null() || key_ptr.is_null() || iv_ptr.is_null() || out_ptr.is_null() {
+        return PTR_ERR_NULL;
+    }
+
+    if len == 0 {
+        return PTR_ERR_EMPTY;
+    }
+
+    if !len.is_multiple_of(16) {
+        return PTR_ERR_UNALIGNED;
+    }
+
+    if len > i32::MAX as usize {
+        return PTR_ERR_TOO_LARGE;
+    }
+
+    // SAFETY: 调用方保证密钥和IV区域可读且长度正确
+    let (key, iv) = unsafe { (std::slice::from_raw_parts(key_ptr, 32), std::slice::from_raw_parts(iv_ptr, 16)) };
+    let Ok(cipher) = Aes256CbcDec::new_from_slices(key, iv) else {
+        return PTR_ERR_KEY;
+    };
+
+    // SAFETY: 调用方保证两个区域都至少有len字节；ptr::copy允许重叠
+    let out = unsafe {
+        std::ptr::copy(encrypted_ptr, out_ptr, len);
+        std::slice::from_raw_parts_mut(out_ptr, len)
+    };
+
+    if cipher.decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(out).is_err() {
+        return PTR_ERR_UNALIGNED;
+    }
+
+    match padding::pkcs7_unpadded_len(out) {
+        Ok(plaintext_len) => plaintext_len as i32,
+        Err(_) => PTR_ERR_PADDING,
+    }
+}
+
+/// 工具函数：尽力清零JS持有的缓冲区
+/// 从WASM侧把该视图覆盖的字节全部写为0，适合在用完解密结果后擦除明文。
+/// 只清零这一个视图范围内的字节：同一ArrayBuffer中视图以外的部分、此前复制出的副本
+/// （如Blob、Canvas或WASM内存中的中间数据）以及垃圾回收器可能留下的旧拷贝都不受影响，
+/// 因此不能保证明文在别处不存在
+/// 
+/// # 参数
+/// - `buf`: 要清零的Uint8Array
+#[wasm_bindgen]
+pub fn secure_wipe(buf: &Uint8Array) {
+    buf.fill(0, 0, buf.length());
+}
+
 /// 工具函数：获取WASM模块信息
 #[wasm_bindgen]
 pub fn get_wasm_info() -> JsValue {
@@ -255,22 +3866,11 @@ pub fn get_wasm_info() -> JsValue {
     js_sys::Reflect::set(&info, &"version".into(), &"1.0.0".into()).unwrap();
     js_sys::Reflect::set(&info, &"simdSupport".into(), &check_simd_support().into()).unwrap();
     js_sys::Reflect::set(&info, &"algorithm".into(), &"AES-256-CBC".into()).unwrap();
+    js_sys::Reflect::set(&info, &"allocator".into(), &ALLOCATOR.into()).unwrap();
     
     info.into()
 }
 
-/// 日志输出到浏览器控制台
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-}
-
-/// 宏：简化控制台日志输出
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
-}
-
 /// 初始化WASM模块
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -278,4 +3878,204 @@ pub fn main() {
     set_panic_hook();
     
     console_log!("🦀 Rust WASM 图片解密模块已加载");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift64伪随机数，固定种子保证每次运行结果一致
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    /// 带有常见文件头的随机数据，让解析器越过签名检查进入字段解析
+    fn random_input(rng: &mut Rng) -> Vec<u8> {
+        const PREFIXES: [&[u8]; 12] = [
+            b"",
+            b"II*\0",
+            b"MM\0*",
+            b"\xFF\xD8\xFF",
+            b"\xFF\xD8\xFF\xE2\0\x40ICC_PROFILE\0",
+            b"\x89PNG\r\n\x1A\n",
+            b"RIFF\0\0\0\0WEBPVP8X",
+            b"GIF89a",
+            b"data:;base64,",
+            b"{\"v\":\"5.7.0\",\"layers\":",
+            b"\0\0\0\x02",
+            b"key:32,iv:16,ct:",
+        ];
+
+        let len = (rng.next() % 512) as usize;
+        let mut data = rng.bytes(len);
+        let prefix = PREFIXES[(rng.next() % PREFIXES.len() as u64) as usize];
+        let n = prefix.len().min(data.len());
+        data[..n].copy_from_slice(&prefix[..n]);
+
+        // 偏移和长度字段取小值或极大值，覆盖越界与溢出两种情况
+        for offset in [4, 8, 128, 132] {
+            if data.len() >= offset + 4 && rng.next() & 1 == 0 {
+                let value = if rng.next() & 1 == 0 { (rng.next() % 64) as u32 } else { u32::MAX - (rng.next() % 16) as u32 };
+                data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn parsers_do_not_panic_on_random_input() {
+        let decryptor = ImageDecryptor::from_config(&DecryptorConfig::default());
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..20_000 {
+            let data = random_input(&mut rng);
+
+            decryptor.detect_image_type(&data);
+            let _ = padding::pkcs7_unpadded_len(&data);
+            let _ = padding::pkcs7_unpadded_len_for(&data, 8);
+            // description和is_p3通过icc::tag查找标签
+            icc::description(&data);
+            icc::is_p3(&data);
+            if let Some(tiff) = tiff::parse(&data) {
+                tiff.ifd0();
+                tiff.ascii(tiff::MAKE);
+            }
+
+            jpeg::inspect(&data);
+            jpeg::validate_structure(&data);
+            jpeg::icc_profile(&data);
+            png::dimensions(&data);
+            png::pixel_format(&data);
+            png::icc_profile(&data);
+            png::is_animated(&data);
+            gif::dimensions(&data);
+            gif::frame_count(&data, usize::MAX);
+            format::webp_dimensions(&data);
+            format::webp_is_animated(&data);
+            let _ = format::sprite_entries(&data);
+            let _ = format::length_prefixed(&data);
+            format::looks_like_lottie(&data);
+            format::looks_like_text(&data);
+
+            let text = String::from_utf8_lossy(&data);
+            let _ = format::parse_layout(&text);
+            let _ = format::data_url_payload(&text);
+        }
+    }
+
+    #[test]
+    fn parsers_do_not_panic_on_short_input() {
+        let decryptor = ImageDecryptor::from_config(&DecryptorConfig::default());
+
+        for len in 0..=16 {
+            let data = vec![0xFF; len];
+            decryptor.detect_image_type(&data);
+            let _ = padding::pkcs7_unpadded_len(&data);
+            icc::description(&data);
+            assert!(tiff::parse(&data).is_none());
+        }
+    }
+
+    #[test]
+    fn empty_ciphertext_is_rejected_before_decryption() {
+        let decryptor = ImageDecryptor::from_config(&DecryptorConfig::default());
+        let iv_base64 = general_purpose::STANDARD.encode([0u8; 16]);
+
+        assert!(matches!(
+            decryptor.validate_cbc_data(0, &iv_base64, IvMode::Explicit),
+            Err(Error::EmptyData)
+        ));
+        assert!(decryptor.validate_cbc_data(16, &iv_base64, IvMode::Explicit).is_ok());
+    }
+
+    const KEY: [u8; 32] = [0x11; 32];
+    const IV: [u8; 16] = [0x22; 16];
+
+    /// 按`pad_block_size`分组做PKCS7填充后用AES-256-CBC加密
+    fn encrypt_padded(plaintext: &[u8], pad_block_size: usize) -> Vec<u8> {
+        use cbc::cipher::BlockEncryptMut;
+
+        let pad = pad_block_size - plaintext.len() % pad_block_size;
+        let mut buffer = plaintext.to_vec();
+        buffer.resize(plaintext.len() + pad, pad as u8);
+        // 填充后还要交给AES，测试数据的长度须选为填充后按16字节对齐
+        assert!(buffer.len().is_multiple_of(16));
+        let len = buffer.len();
+        cbc::Encryptor::<Aes256>::new_from_slices(&KEY, &IV)
+            .unwrap()
+            .encrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer, len)
+            .unwrap();
+        buffer
+    }
+
+    /// 与decrypt_to_vec相同的整体解密：解密所有块后由strip_padding移除填充
+    fn decrypt_whole(decryptor: &ImageDecryptor, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buffer = ciphertext.to_vec();
+        Aes256CbcDec::new_from_slices(&KEY, &IV)
+            .unwrap()
+            .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer)
+            .unwrap();
+        decryptor.unpad(buffer, Vec::new)
+    }
+
+    fn decrypt_incremental(decryptor: &ImageDecryptor, ciphertext: &[u8], chunk_size: usize) -> Result<Vec<u8>, Error> {
+        let mut incremental = decryptor.new_incremental(KEY.to_vec(), IV.to_vec());
+        let mut plaintext = Vec::new();
+        for chunk in ciphertext.chunks(chunk_size) {
+            plaintext.extend(incremental.feed(chunk)?);
+        }
+        plaintext.extend(incremental.finish()?);
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn incremental_path_honours_padding_block_size() {
+        let mut decryptor = ImageDecryptor::from_config(&DecryptorConfig::default());
+        decryptor.pad_block_size = 8;
+
+        // 28字节明文按8字节分组填充4字节，共32字节
+        let plaintext: Vec<u8> = (0..28).collect();
+        let ciphertext = encrypt_padded(&plaintext, 8);
+
+        assert_eq!(decrypt_whole(&decryptor, &ciphertext).unwrap(), plaintext);
+        for chunk_size in [1, 15, 16, 17, ciphertext.len()] {
+            assert_eq!(decrypt_incremental(&decryptor, &ciphertext, chunk_size).unwrap(), plaintext);
+        }
+
+        // 按16字节分组填充的12字节填充超过8，两条路径都应报错
+        let ciphertext = encrypt_padded(&[0x33; 20], 16);
+        assert!(matches!(decrypt_whole(&decryptor, &ciphertext), Err(Error::Padding { .. })));
+        assert!(matches!(decrypt_incremental(&decryptor, &ciphertext, 16), Err(Error::Padding { .. })));
+    }
+
+    #[test]
+    fn incremental_path_trims_trailing_newline() {
+        let mut decryptor = ImageDecryptor::from_config(&DecryptorConfig::default());
+        decryptor.trim_trailing_newline = true;
+
+        // 33字节明文的末块只含"\n"，"\r"位于前一块
+        let mut plaintext: Vec<u8> = (b'a'..).take(31).collect();
+        plaintext.extend_from_slice(b"\r\n");
+
+        for text in [plaintext.clone(), b"line\n".to_vec(), b"no newline".to_vec(), b"\n".to_vec()] {
+            let ciphertext = encrypt_padded(&text, 16);
+            let expected = decrypt_whole(&decryptor, &ciphertext).unwrap();
+            assert_eq!(expected, text[..text.len() - padding::trailing_newline_len(&text)]);
+            for chunk_size in [1, 15, 16, 17, ciphertext.len()] {
+                assert_eq!(decrypt_incremental(&decryptor, &ciphertext, chunk_size).unwrap(), expected);
+            }
+        }
+    }
+}
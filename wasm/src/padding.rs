@@ -0,0 +1,130 @@
+use crate::error::Error;
+
+/// AES分组长度（字节）
+pub(crate) const BLOCK_SIZE: usize = 16;
+
+/// PKCS7填充校验失败的具体原因
+#[derive(Debug)]
+pub(crate) enum PaddingFault {
+    /// 填充长度不在1~`max`范围内，`max`为填充分组长度
+    Length { pad: u8, max: usize },
+    /// 某个填充字节与填充长度不一致，`position`为从末尾起的位置（1开始）
+    Byte { position: usize, found: u8, expected: u8 },
+}
+
+/// 严格校验PKCS7填充并返回去除填充后的长度
+/// 要求填充长度在1~16之间，且所有填充字节都等于填充长度；
+/// 空数据或比填充长度更短的数据视为填充长度错误，不会越界
+pub(crate) fn pkcs7_unpadded_len(data: &[u8]) -> Result<usize, PaddingFault> {
+    pkcs7_unpadded_len_for(data, BLOCK_SIZE)
+}
+
+/// 按指定的填充分组长度校验PKCS7填充并返回去除填充后的长度
+/// 用于先按8字节分组填充、再经AES加密的历史数据，此时填充长度只能在1~8之间
+pub(crate) fn pkcs7_unpadded_len_for(data: &[u8], block_size: usize) -> Result<usize, PaddingFault> {
+    let Some(&pad) = data.last() else {
+        return Err(PaddingFault::Length { pad: 0, max: block_size });
+    };
+    let pad_len = pad as usize;
+
+    if pad_len == 0 || pad_len > block_size || pad_len > data.len() {
+        return Err(PaddingFault::Length { pad, max: block_size });
+    }
+
+    for position in 1..=pad_len {
+        let found = data[data.len() - position];
+        if found != pad {
+            return Err(PaddingFault::Byte { position, found, expected: pad });
+        }
+    }
+
+    Ok(data.len() - pad_len)
+}
+
+/// PKCS7为指定长度的明文追加的填充字节数，明文按16字节对齐时为一整块（16）
+pub(crate) fn pkcs7_pad_len(plaintext_len: usize) -> usize {
+    BLOCK_SIZE - plaintext_len % BLOCK_SIZE
+}
+
+/// 校验填充分组长度，只支持8（DES时代的工具）和16（AES）
+pub(crate) fn check_block_size(size: usize) -> Result<usize, Error> {
+    match size {
+        8 | BLOCK_SIZE => Ok(size),
+        _ => Err(Error::PaddingBlockSize(size)),
+    }
+}
+
+/// 明文末尾换行符的字节数：`\r\n`为2，`\n`为1，没有换行符时为0
+pub(crate) fn trailing_newline_len(data: &[u8]) -> usize {
+    if data.ends_with(b"\r\n") {
+        2
+    } else if data.ends_with(b"\n") {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 12字节明文按16字节分组填充后的数据块
+    fn padded_block() -> Vec<u8> {
+        let mut block = b"hello, world".to_vec();
+        block.extend_from_slice(&[4; 4]);
+        block
+    }
+
+    #[test]
+    fn accepts_valid_padding() {
+        assert!(matches!(pkcs7_unpadded_len(&padded_block()), Ok(12)));
+    }
+
+    #[test]
+    fn rejects_mismatched_pad_byte() {
+        let mut block = padded_block();
+        block[13] = 3;
+        assert!(matches!(
+            pkcs7_unpadded_len(&block),
+            Err(PaddingFault::Byte { position: 3, found: 3, expected: 4 })
+        ));
+    }
+
+    #[test]
+    fn rejects_zero_pad_length() {
+        let mut block = padded_block();
+        block[15] = 0;
+        assert!(matches!(pkcs7_unpadded_len(&block), Err(PaddingFault::Length { pad: 0, max: 16 })));
+    }
+
+    #[test]
+    fn rejects_pad_length_over_block_size() {
+        let block = [17u8; 32];
+        assert!(matches!(pkcs7_unpadded_len(&block), Err(PaddingFault::Length { pad: 17, max: 16 })));
+    }
+
+    #[test]
+    fn rejects_pad_length_over_eight_byte_block() {
+        let mut block = [0u8; 16];
+        block[4..].fill(12);
+        assert!(matches!(pkcs7_unpadded_len(&block), Ok(4)));
+        assert!(matches!(pkcs7_unpadded_len_for(&block, 8), Err(PaddingFault::Length { pad: 12, max: 8 })));
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert!(matches!(pkcs7_unpadded_len(&[]), Err(PaddingFault::Length { pad: 0, max: 16 })));
+    }
+
+    #[test]
+    fn all_padding_block_unpads_to_empty() {
+        assert!(matches!(pkcs7_unpadded_len(&[16; 16]), Ok(0)));
+        assert!(matches!(pkcs7_unpadded_len_for(&[8; 8], 8), Ok(0)));
+    }
+
+    #[test]
+    fn rejects_pad_length_over_data_length() {
+        assert!(matches!(pkcs7_unpadded_len(&[5; 4]), Err(PaddingFault::Length { pad: 5, max: 16 })));
+    }
+}
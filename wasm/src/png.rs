@@ -0,0 +1,115 @@
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+
+use crate::format::PixelFormat;
+
+/// iCCP中ICC配置文件解压后的最大字节数，常见配置文件远小于该值
+const MAX_ICC_PROFILE_SIZE: usize = 4 * 1024 * 1024;
+
+/// PNG文件签名
+pub(crate) const SIGNATURE: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// 完整的IEND数据块：长度0 + "IEND" + 固定的CRC
+pub(crate) const IEND_CHUNK: &[u8] = &[0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82];
+
+/// 一个PNG数据块
+pub(crate) struct Chunk<'a> {
+    pub(crate) kind: [u8; 4],
+    pub(crate) data: &'a [u8],
+}
+
+/// 按顺序遍历PNG数据块，不校验CRC，遇到IEND或数据截断时停止
+pub(crate) struct Chunks<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+/// 遍历PNG数据块，数据不以PNG签名开头时返回None
+pub(crate) fn chunks(data: &[u8]) -> Option<Chunks<'_>> {
+    if !data.starts_with(SIGNATURE) {
+        return None;
+    }
+
+    Some(Chunks { data, pos: SIGNATURE.len() })
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = Chunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 长度(4) + 类型(4) + 数据 + CRC(4)
+        let header = self.data.get(self.pos..self.pos.checked_add(8)?)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = [header[4], header[5], header[6], header[7]];
+
+        let start = self.pos + 8;
+        let end = start.checked_add(len)?;
+        let data = self.data.get(start..end)?;
+        // CRC之后的位置溢出时停在数据末尾，下一次调用结束遍历
+        self.pos = if &kind == b"IEND" { self.data.len() } else { end.saturating_add(4) };
+
+        Some(Chunk { kind, data })
+    }
+}
+
+/// 解析IHDR中的图片尺寸，IHDR必须是第一个数据块
+pub(crate) fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let chunk = chunks(data)?.next().filter(|chunk| &chunk.kind == b"IHDR")?;
+    let header = chunk.data.get(..8)?;
+
+    let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    Some((width, height))
+}
+
+/// 解析IHDR中的位深和颜色类型
+/// IHDR数据为：宽(4) + 高(4) + 位深(1) + 颜色类型(1) + ...
+pub(crate) fn pixel_format(data: &[u8]) -> Option<PixelFormat> {
+    let chunk = chunks(data)?.next().filter(|chunk| &chunk.kind == b"IHDR")?;
+    let (&bit_depth, &color_type) = (chunk.data.get(8)?, chunk.data.get(9)?);
+
+    let (channels, color_type) = match color_type {
+        0 => (1, "grayscale"),
+        2 => (3, "rgb"),
+        3 => (1, "palette"),
+        4 => (2, "grayscale-alpha"),
+        6 => (4, "rgba"),
+        _ => return None,
+    };
+
+    Some(PixelFormat { bit_depth, channels, color_type })
+}
+
+/// 提取iCCP块中的ICC配置文件并解压
+/// iCCP块为：配置文件名(1~79字节) + 0 + 压缩方式(1字节，只定义了0即zlib) + 压缩数据
+/// 解压结果超过4MB时返回None
+pub(crate) fn icc_profile(data: &[u8]) -> Option<Vec<u8>> {
+    let chunk = chunks(data)?
+        .take_while(|chunk| &chunk.kind != b"IDAT")
+        .find(|chunk| &chunk.kind == b"iCCP")?;
+
+    let name_end = chunk.data.iter().position(|&byte| byte == 0)?;
+    let (&method, compressed) = chunk.data[name_end + 1..].split_first()?;
+    if method != 0 {
+        return None;
+    }
+
+    let mut profile = Vec::new();
+    ZlibDecoder::new(compressed)
+        .take(MAX_ICC_PROFILE_SIZE as u64 + 1)
+        .read_to_end(&mut profile)
+        .ok()?;
+
+    // 超过上限的配置文件视为无效，避免解压炸弹耗尽内存
+    (profile.len() <= MAX_ICC_PROFILE_SIZE).then_some(profile)
+}
+
+/// 是否为APNG：IDAT之前出现acTL动画控制块
+pub(crate) fn is_animated(data: &[u8]) -> bool {
+    chunks(data).is_some_and(|chunks| {
+        chunks
+            .take_while(|chunk| &chunk.kind != b"IDAT")
+            .any(|chunk| &chunk.kind == b"acTL")
+    })
+}
@@ -0,0 +1,45 @@
+use aes::Aes256;
+use cbc::cipher::KeyInit;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Locale};
+use crate::ImageDecryptor;
+
+/// 预先完成密钥扩展的AES-256密钥
+/// 配合`ImageDecryptor::decrypt_with_prepared`使用：批量解密同一密钥、不同IV的数据时，
+/// 密钥扩展只需执行一次，每次解密只需按各自的IV初始化CBC状态
+#[wasm_bindgen]
+pub struct PreparedKey {
+    cipher: Aes256,
+}
+
+#[wasm_bindgen]
+impl PreparedKey {
+    /// 解码密钥并执行AES密钥扩展
+    /// 
+    /// # 参数
+    /// - `key_base64`: Base64编码的32字节密钥
+    #[wasm_bindgen(constructor)]
+    pub fn new(key_base64: &str) -> Result<PreparedKey, JsValue> {
+        Self::create(key_base64).map_err(|e| e.to_js(Locale::default()))
+    }
+}
+
+impl PreparedKey {
+    /// 解码密钥并创建PreparedKey
+    pub(crate) fn create(key_base64: &str) -> Result<PreparedKey, Error> {
+        if key_base64.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+
+        let key = ImageDecryptor::decode_key(key_base64)?;
+        let cipher = Aes256::new_from_slice(&key).map_err(|e| Error::CipherInit(e.to_string()))?;
+
+        Ok(PreparedKey { cipher })
+    }
+
+    /// 已完成密钥扩展的分组密码，每次解密复制一份作为CBC的内部状态
+    pub(crate) fn cipher(&self) -> Aes256 {
+        self.cipher.clone()
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::{WebPDecoder, WebPEncoder};
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+
+use crate::error::Error;
+
+/// 完整解码图片数据
+/// 根据文件头自动识别格式，解码失败时返回错误描述
+pub(crate) fn decode(data: &[u8]) -> Result<DynamicImage, Error> {
+    image::load_from_memory(data).map_err(|e| Error::Decode(e.to_string()))
+}
+
+/// 文件头对应的格式是否可由本模块完整解码（BMP、GIF、JPEG、PNG、WebP）
+pub(crate) fn can_decode(data: &[u8]) -> bool {
+    image::guess_format(data).is_ok_and(|format| format.reading_enabled())
+}
+
+/// 行对齐字节数的上限，即WebGPU缓冲区到纹理复制要求的bytesPerRow对齐
+pub(crate) const MAX_ROW_ALIGNMENT: u32 = 256;
+
+/// 检查行对齐字节数，只接受不超过256的2的幂（1、2、4、8……256）
+pub(crate) fn check_row_alignment(row_alignment: u32) -> Result<(), Error> {
+    if row_alignment.is_power_of_two() && row_alignment <= MAX_ROW_ALIGNMENT {
+        Ok(())
+    } else {
+        Err(Error::InvalidRowAlignment(row_alignment))
+    }
+}
+
+/// 将图片转换为RGBA像素，并把每行补零至`row_alignment`字节的整数倍
+/// 返回像素数据和补齐后的行跨度（字节）；行对齐不合法或缓冲区大小溢出时返回错误
+pub(crate) fn to_rgba_aligned(image: &DynamicImage, row_alignment: u32) -> Result<(Vec<u8>, usize), Error> {
+    check_row_alignment(row_alignment)?;
+
+    let rgba = image.to_rgba8();
+    let row_alignment = row_alignment as usize;
+    let row_bytes = (rgba.width() as usize)
+        .checked_mul(4)
+        .ok_or(Error::PixelBufferTooLarge)?;
+    let stride = row_bytes
+        .div_ceil(row_alignment)
+        .checked_mul(row_alignment)
+        .ok_or(Error::PixelBufferTooLarge)?;
+
+    if stride == row_bytes {
+        return Ok((rgba.into_raw(), stride));
+    }
+
+    let len = stride
+        .checked_mul(rgba.height() as usize)
+        .ok_or(Error::PixelBufferTooLarge)?;
+    let mut pixels = vec![0u8; len];
+    for (src, dst) in rgba.as_raw().chunks_exact(row_bytes).zip(pixels.chunks_exact_mut(stride)) {
+        dst[..row_bytes].copy_from_slice(src);
+    }
+
+    Ok((pixels, stride))
+}
+
+/// 将图片转换为预乘Alpha的RGBA像素
+/// 每个颜色通道乘以alpha/255并四舍五入；没有Alpha通道的图片直接返回RGBA像素
+pub(crate) fn to_premultiplied_rgba(image: &DynamicImage) -> Vec<u8> {
+    let mut pixels = image.to_rgba8().into_raw();
+    if !image.color().has_alpha() {
+        return pixels;
+    }
+
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = u32::from(pixel[3]);
+        for channel in &mut pixel[..3] {
+            *channel = ((u32::from(*channel) * alpha + 127) / 255) as u8;
+        }
+    }
+
+    pixels
+}
+
+/// 将图片切分为`tile_size`×`tile_size`的瓦片，按行优先顺序返回瓦片左上角坐标和瓦片图像
+/// 右侧和底部边缘的瓦片可能小于`tile_size`
+pub(crate) fn tiles(image: &DynamicImage, tile_size: u32) -> Vec<(u32, u32, DynamicImage)> {
+    let (width, height) = (image.width(), image.height());
+
+    (0..height)
+        .step_by(tile_size as usize)
+        .flat_map(|y| (0..width).step_by(tile_size as usize).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let tile = image.crop_imm(x, y, tile_size.min(width - x), tile_size.min(height - y));
+            (x, y, tile)
+        })
+        .collect()
+}
+
+/// 计算图片的平均颜色，用作加载占位背景色
+/// 先缩小为不超过16×16的缩略图再按Alpha加权平均，完全透明的像素不参与计算；
+/// 整张图完全透明时返回黑色
+pub(crate) fn average_color(image: &DynamicImage) -> [u8; 3] {
+    let thumbnail = image.thumbnail(16, 16).into_rgba8();
+
+    let mut sums = [0u64; 3];
+    let mut weight = 0u64;
+    for pixel in thumbnail.pixels() {
+        let alpha = u64::from(pixel[3]);
+        for (sum, &channel) in sums.iter_mut().zip(&pixel.0[..3]) {
+            *sum += u64::from(channel) * alpha;
+        }
+        weight += alpha;
+    }
+
+    if weight == 0 {
+        return [0; 3];
+    }
+    sums.map(|sum| ((sum + weight / 2) / weight) as u8)
+}
+
+/// 计算均值哈希（aHash）
+/// 缩放为8×8灰度图，按行优先顺序每个像素不小于均值时记为1，最高位对应左上角
+pub(crate) fn average_hash(image: &DynamicImage) -> u64 {
+    let gray = image.resize_exact(8, 8, FilterType::Triangle).into_luma8();
+    let pixels = gray.as_raw();
+    let mean = pixels.iter().map(|&p| u32::from(p)).sum::<u32>() / pixels.len() as u32;
+
+    pixels.iter().fold(0u64, |hash, &p| (hash << 1) | u64::from(u32::from(p) >= mean))
+}
+
+/// 计算差值哈希（dHash）
+/// 缩放为9×8灰度图，每行相邻像素左侧比右侧亮时记为1，最高位对应左上角
+pub(crate) fn difference_hash(image: &DynamicImage) -> u64 {
+    let gray = image.resize_exact(9, 8, FilterType::Triangle).into_luma8();
+
+    gray.as_raw()
+        .chunks_exact(9)
+        .flat_map(|row| row.windows(2).map(|pair| pair[0] > pair[1]))
+        .fold(0u64, |hash, bit| (hash << 1) | u64::from(bit))
+}
+
+/// 将图片编码为指定格式
+pub(crate) fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, Error> {
+    let mut output = Cursor::new(Vec::new());
+    image
+        .write_to(&mut output, format)
+        .map_err(|e| Error::Encode(e.to_string()))?;
+    Ok(output.into_inner())
+}
+
+/// AVIF编码速度（1~10，越大越快），在浏览器内优先保证响应速度
+#[cfg(feature = "avif")]
+const AVIF_SPEED: u8 = 8;
+
+/// 按目标格式名称重新编码图片
+/// 支持"png"、"jpeg"（或"jpg"）、"webp"，启用`avif`特性后支持"avif"。
+/// `quality`（1~100）用于JPEG和AVIF；PNG无损，WebP编码器只支持无损压缩，均忽略该参数
+pub(crate) fn transcode(image: &DynamicImage, target: &str, quality: u8) -> Result<Vec<u8>, Error> {
+    let quality = quality.clamp(1, 100);
+    let mut output = Vec::new();
+
+    let result = match target.to_ascii_lowercase().as_str() {
+        "png" => return encode(image, ImageFormat::Png),
+        // JPEG不支持透明通道，先丢弃alpha
+        "jpeg" | "jpg" => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut output, quality)),
+        "webp" => DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(&mut output)),
+        #[cfg(feature = "avif")]
+        "avif" => image.write_with_encoder(image::codecs::avif::AvifEncoder::new_with_speed_quality(
+            &mut output,
+            AVIF_SPEED,
+            quality,
+        )),
+        _ => return Err(Error::UnsupportedFormat(target.to_string())),
+    };
+
+    result.map_err(|e| Error::Encode(e.to_string()))?;
+    Ok(output)
+}
+
+/// 解析WebP的所有帧
+/// 返回每帧合成后的完整画面及其显示时长（毫秒），静态WebP返回时长为0的单帧
+pub(crate) fn webp_frames(data: &[u8]) -> Result<Vec<(DynamicImage, u32)>, Error> {
+    let decoder = WebPDecoder::new(Cursor::new(data)).map_err(|e| Error::Decode(e.to_string()))?;
+
+    if !decoder.has_animation() {
+        let image = DynamicImage::from_decoder(decoder).map_err(|e| Error::Decode(e.to_string()))?;
+        return Ok(vec![(image, 0)]);
+    }
+
+    decoder
+        .into_frames()
+        .map(|frame| {
+            let frame = frame.map_err(|e| Error::Decode(e.to_string()))?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let duration = numer.checked_div(denom).unwrap_or(0);
+            Ok((DynamicImage::ImageRgba8(frame.into_buffer()), duration))
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+
+use crate::{cbc_hmac, Aes256CbcDec};
+
+/// NIST SP 800-38A F.2.6 CBC-AES256.Decrypt 的密钥
+const KEY: &str = "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4";
+
+/// 已知答案测试向量
+struct Vector {
+    name: &'static str,
+    iv: &'static str,
+    ciphertext: &'static str,
+    plaintext: &'static str,
+}
+
+/// 取自NIST SP 800-38A F.2.6：完整的4块、单独首块，以及以前一密文块为IV的后两块（验证块间链接）
+const VECTORS: [Vector; 3] = [
+    Vector {
+        name: "SP800-38A F.2.6 blocks 1-4",
+        iv: "000102030405060708090a0b0c0d0e0f",
+        ciphertext: "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+                     39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+        plaintext: "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                    30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    },
+    Vector {
+        name: "SP800-38A F.2.6 block 1",
+        iv: "000102030405060708090a0b0c0d0e0f",
+        ciphertext: "f58c4c04d6e5f1ba779eabfb5f7bfbd6",
+        plaintext: "6bc1bee22e409f96e93d7e117393172a",
+    },
+    Vector {
+        name: "SP800-38A F.2.6 blocks 3-4",
+        iv: "9cfc4e967edb808d679f777bc6702c7d",
+        ciphertext: "39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+        plaintext: "30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    },
+];
+
+/// AES_256_CBC_HMAC_SHA256记录的已知答案：加密密钥同上，MAC密钥为0x00~0x1f，
+/// IV同F.2.6，明文为"AES_256_CBC_HMAC_SHA256 known answer"，AAD为"record header"
+const CBC_HMAC_MAC_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+const CBC_HMAC_AAD: &str = "7265636f726420686561646572";
+const CBC_HMAC_RECORD: &str = "000102030405060708090a0b0c0d0e0f4ff14ec6f16a7fe994af8b1646a31eb6\
+                               dc722be310de5a76ba8cac9252075cbebe93f1ad1553a07744ef814540fe63d5\
+                               1edf82c4c1df80a0f5597034438eaf936716d58767df4d159288c12bd42f29f6";
+const CBC_HMAC_PLAINTEXT: &str = "4145535f3235365f4342435f484d41435f534841323536206b6e6f776e20616e73776572";
+
+/// 解码测试向量中的十六进制字符串，忽略续行产生的空白
+fn from_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    digits
+        .chunks_exact(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap_or("zz"), 16).unwrap_or(0))
+        .collect()
+}
+
+/// 运行全部已知答案测试，返回未通过的向量名称
+pub(crate) fn run() -> Vec<&'static str> {
+    let key = from_hex(KEY);
+
+    let mut failures: Vec<&'static str> = VECTORS
+        .iter()
+        .filter(|vector| {
+            let mut buffer = from_hex(vector.ciphertext);
+            let decrypted = Aes256CbcDec::new_from_slices(&key, &from_hex(vector.iv))
+                .ok()
+                .and_then(|cipher| cipher.decrypt_padded_mut::<NoPadding>(&mut buffer).ok().map(|plain| plain.to_vec()));
+            decrypted != Some(from_hex(vector.plaintext))
+        })
+        .map(|vector| vector.name)
+        .collect();
+
+    let opened = cbc_hmac::open(&key, &from_hex(CBC_HMAC_MAC_KEY), &from_hex(CBC_HMAC_RECORD), &from_hex(CBC_HMAC_AAD));
+    if opened.ok() != Some(from_hex(CBC_HMAC_PLAINTEXT)) {
+        failures.push("AES_256_CBC_HMAC_SHA256 record");
+    }
+
+    failures
+}
@@ -0,0 +1,87 @@
+/// DNGVersion标签，DNG规范要求其位于IFD0
+pub(crate) const DNG_VERSION: u16 = 0xC612;
+
+/// Make标签（相机厂商，ASCII）
+pub(crate) const MAKE: u16 = 0x010F;
+
+/// ASCII字段类型
+const TYPE_ASCII: u16 = 2;
+
+/// IFD中单个条目占用的字节数：标签(2) + 类型(2) + 数量(4) + 值或偏移(4)
+const IFD_ENTRY_SIZE: usize = 12;
+
+/// 一个IFD条目
+pub(crate) struct IfdEntry {
+    pub(crate) tag: u16,
+    field_type: u16,
+    count: u32,
+    // 条目中值或偏移字段的位置，值不超过4字节时直接存放在这里
+    value_offset: usize,
+}
+
+/// 已确认TIFF文件头的数据，记录字节序
+pub(crate) struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+/// 检查TIFF文件头（"II*\0"或"MM\0*"），不是TIFF时返回None
+pub(crate) fn parse(data: &[u8]) -> Option<Tiff<'_>> {
+    let little_endian = match data.get(..4)? {
+        [b'I', b'I', 0x2A, 0x00] => true,
+        [b'M', b'M', 0x00, 0x2A] => false,
+        _ => return None,
+    };
+
+    Some(Tiff { data, little_endian })
+}
+
+impl<'a> Tiff<'a> {
+    fn read_u16(&self, offset: usize) -> Option<u16> {
+        let bytes = [*self.data.get(offset)?, *self.data.get(offset.checked_add(1)?)?];
+        Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn read_u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// 读取IFD0的全部条目，偏移越界或条目表截断时返回None
+    pub(crate) fn ifd0(&self) -> Option<Vec<IfdEntry>> {
+        let offset = self.read_u32(4)? as usize;
+        let count = self.read_u16(offset)? as usize;
+
+        (0..count)
+            .map(|i| {
+                let entry = offset.checked_add(2 + i * IFD_ENTRY_SIZE)?;
+                Some(IfdEntry {
+                    tag: self.read_u16(entry)?,
+                    field_type: self.read_u16(entry.checked_add(2)?)?,
+                    count: self.read_u32(entry.checked_add(4)?)?,
+                    value_offset: entry.checked_add(8)?,
+                })
+            })
+            .collect()
+    }
+
+    /// IFD0中是否包含指定标签
+    pub(crate) fn has_tag(&self, tag: u16) -> bool {
+        self.ifd0().is_some_and(|entries| entries.iter().any(|entry| entry.tag == tag))
+    }
+
+    /// 读取IFD0中ASCII标签的值（去掉末尾的NUL和空格），标签不存在或不是ASCII类型时返回None
+    pub(crate) fn ascii(&self, tag: u16) -> Option<String> {
+        let entry = self.ifd0()?.into_iter().find(|entry| entry.tag == tag && entry.field_type == TYPE_ASCII)?;
+        let len = entry.count as usize;
+        let start = if len <= 4 { entry.value_offset } else { self.read_u32(entry.value_offset)? as usize };
+        let bytes = self.data.get(start..start.checked_add(len)?)?;
+
+        Some(String::from_utf8_lossy(bytes).trim_end_matches(['\0', ' ']).to_string())
+    }
+
+    /// 是否为Canon CR2：文件头第8~9字节为"CR"
+    pub(crate) fn is_cr2(&self) -> bool {
+        self.data.get(8..10) == Some(b"CR")
+    }
+}